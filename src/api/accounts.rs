@@ -1,68 +1,53 @@
-use http_body_util::{Empty, Full};
-use hyper::{body::Bytes, Method, Request};
-use hyper_util::client::legacy::Client;
-use hyperlocal_with_windows::{UnixClientExt, UnixConnector, Uri};
-use serde::Serialize;
-use serde_json::Value;
-
-use crate::utils::misc;
-
-use super::common::{ActionResponse, ErrorResponse};
+//! Managing the accounts registered with Octyne.
 
-pub async fn get_accounts() -> Result<Vec<String>, String> {
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let uri = Uri::new(misc::default_octyne_path(), "/accounts").into();
-    let response = client.get(uri).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: Value = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(format!("Received corrupt response from Octyne! {}", e));
-        }
-    };
+use http_body_util::Full;
+use hyper::Method;
+use serde::Serialize;
 
-    if json.is_object() {
-        let resp: ErrorResponse = match serde_json::from_value(json) {
-            Ok(res) => res,
-            Err(e) => return Err(format!("Received corrupt response from Octyne! {}", e)),
-        };
-        if resp.error.is_empty() {
-            return Err("Received corrupt response from Octyne!".to_string());
-        } else {
-            return Err(resp.error);
-        }
-    } else if res.status() != 200 {
-        return Err(format!(
-            "Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
-    }
+use super::client::{encode_component, OctyneClient};
+use super::common::ApiError;
 
-    match serde_json::from_value(json) {
-        Ok(accounts) => Ok(accounts),
-        Err(err) => Err(format!("Received corrupt response from Octyne! {}", err)),
-    }
+/// Lists the usernames of all accounts.
+pub async fn get_accounts(client: &OctyneClient) -> Result<Vec<String>, ApiError> {
+    client
+        .request_json(Method::GET, "/accounts", Full::default())
+        .await
 }
 
-pub async fn post_account(username: String, password: String) -> Result<(), String> {
-    let ok = post_patch_account(None, username, password, Method::POST).await?;
-    if !ok {
-        return Err("Octyne failed to create the account!".to_owned());
-    }
-    Ok(())
+/// Creates an account.
+pub async fn post_account(
+    client: &OctyneClient,
+    username: String,
+    password: String,
+) -> Result<(), ApiError> {
+    post_patch_account(
+        client,
+        None,
+        username,
+        password,
+        Method::POST,
+        "Octyne failed to create the account!",
+    )
+    .await
 }
 
+/// Modifies an account, renaming `old_user` to `username` if given, and changing its password
+/// unless `password` is empty.
 pub async fn patch_account(
+    client: &OctyneClient,
     old_user: Option<String>,
     username: String,
     password: String,
-) -> Result<(), String> {
-    let ok = post_patch_account(old_user, username, password, Method::PATCH).await?;
-    if !ok {
-        return Err("Octyne failed to modify the account!".to_owned());
-    }
-    Ok(())
+) -> Result<(), ApiError> {
+    post_patch_account(
+        client,
+        old_user,
+        username,
+        password,
+        Method::PATCH,
+        "Octyne failed to modify the account!",
+    )
+    .await
 }
 
 #[derive(Serialize, Debug)]
@@ -73,73 +58,35 @@ struct PostAccountRequest {
 }
 
 async fn post_patch_account(
+    client: &OctyneClient,
     old_user: Option<String>,
     username: String,
     password: String,
     method: Method,
-) -> Result<bool, String> {
+    failure: &str,
+) -> Result<(), ApiError> {
     let body = match serde_json::to_string(&PostAccountRequest { username, password }) {
         Ok(body) => body,
-        Err(e) => return Err(e.to_string()),
+        Err(e) => return Err(ApiError::Protocol(e.to_string())),
     };
     let mut endpoint = "/accounts".to_string();
     if let Some(val) = old_user {
-        endpoint = format!("/accounts?username={}", val);
-    }
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let req = Request::builder()
-        .method(method)
-        .uri(Uri::new(misc::default_octyne_path(), endpoint.as_str()))
-        .body(Full::from(body))
-        .expect("request builder");
-    let response = client.request(req).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: ActionResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(format!("Received corrupt response from Octyne! {}", e));
-        }
-    };
-
-    if res.status() != 200 && json.error.is_empty() {
-        return Err(format!(
-            "Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
-    } else if !json.error.is_empty() {
-        return Err(json.error);
+        endpoint = format!("/accounts?username={}", encode_component(&val));
     }
-    Ok(json.success)
+    client
+        .request_action(method, &endpoint, Full::from(body), failure)
+        .await
 }
 
-pub async fn delete_account(username: String) -> Result<(), String> {
-    let endpoint = format!("/accounts?username={}", username);
-    let client: Client<UnixConnector, Empty<Bytes>> = Client::unix();
-    let req = Request::builder()
-        .method(Method::DELETE)
-        .uri(Uri::new(misc::default_octyne_path(), endpoint.as_str()))
-        .body(Empty::new())
-        .expect("request builder");
-    let response = client.request(req).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: ActionResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(format!("Received corrupt response from Octyne! {}", e));
-        }
-    };
-
-    if res.status() != 200 && json.error.is_empty() {
-        return Err(format!(
-            "Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
-    } else if !json.error.is_empty() {
-        return Err(json.error);
-    } else if !json.success {
-        return Err("Octyne failed to delete the account!".to_owned());
-    }
-    Ok(())
+/// Deletes an account.
+pub async fn delete_account(client: &OctyneClient, username: String) -> Result<(), ApiError> {
+    let endpoint = format!("/accounts?username={}", encode_component(&username));
+    client
+        .request_action(
+            Method::DELETE,
+            &endpoint,
+            Full::default(),
+            "Octyne failed to delete the account!",
+        )
+        .await
 }
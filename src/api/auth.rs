@@ -0,0 +1,49 @@
+//! Logging in to and out of Octyne, to obtain tokens for its HTTP(S) API.
+
+use http_body_util::Full;
+use hyper::Method;
+use serde::Deserialize;
+
+use super::client::{parse_response, OctyneClient};
+use super::common::ApiError;
+
+#[derive(Deserialize, Debug)]
+struct LoginResponse {
+    #[serde(default)]
+    token: String,
+}
+
+/// Logs in to Octyne, returning a token for use with [`Transport::Remote`](super::transport::Transport::Remote).
+pub async fn get_login(
+    client: &OctyneClient,
+    username: String,
+    password: String,
+) -> Result<String, ApiError> {
+    let headers = [
+        ("Username", username.as_str()),
+        ("Password", password.as_str()),
+    ];
+    let (status, body) = client
+        .request_with_headers(Method::GET, "/login", Full::default(), &headers)
+        .await?;
+
+    let json: LoginResponse = parse_response("/login", status, &body)?;
+    if json.token.is_empty() {
+        return Err(ApiError::Protocol(
+            "Received no token from Octyne!".to_owned(),
+        ));
+    }
+    Ok(json.token)
+}
+
+/// Logs out of Octyne, invalidating the token used by the client.
+pub async fn get_logout(client: &OctyneClient) -> Result<(), ApiError> {
+    client
+        .request_action(
+            Method::GET,
+            "/logout",
+            Full::default(),
+            "Octyne failed to log out!",
+        )
+        .await
+}
@@ -0,0 +1,286 @@
+//! The [`OctyneClient`] used to make requests to Octyne.
+
+use std::sync::Arc;
+
+#[cfg(target_family = "windows")]
+use super::unix_stream_windows::TokioCompatUnixStream as UnixStream;
+use http_body_util::Full;
+use hyper::{body::Bytes, Method, Request, StatusCode};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use hyperlocal_with_windows::{UnixClientExt, UnixConnector, Uri};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rustls::{ClientConfig, RootCertStore};
+use serde::de::DeserializeOwned;
+use tokio::net::TcpStream;
+#[cfg(target_family = "unix")]
+use tokio::net::UnixStream;
+use tokio_tungstenite::{
+    client_async_tls_with_config, tungstenite::ClientRequestBuilder, Connector,
+};
+
+use super::common::{ActionResponse, ApiError, ErrorResponse};
+use super::transport::{OctyneWebSocket, Stream, Transport};
+
+// Characters which are percent-encoded in app names, usernames etc. in endpoints.
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[derive(Clone, Debug)]
+enum HttpClient {
+    Unix(Client<UnixConnector, Full<Bytes>>),
+    Remote(Client<HttpsConnector<HttpConnector>, Full<Bytes>>),
+}
+
+/// Client for the Octyne API over a [`Transport`].
+///
+/// Connections are pooled and reused across requests, so a single client should be used for all
+/// requests to the same Octyne instance. Cloning the client is cheap and shares the pool.
+#[derive(Clone, Debug)]
+pub struct OctyneClient {
+    transport: Transport,
+    http: HttpClient,
+}
+
+impl OctyneClient {
+    /// Creates a client for the Octyne instance reachable over `transport`.
+    pub fn new(transport: Transport) -> OctyneClient {
+        let http = match transport {
+            Transport::Unix(_) => HttpClient::Unix(Client::unix()),
+            Transport::Remote { .. } => {
+                let connector = HttpsConnectorBuilder::new()
+                    .with_tls_config(tls_config())
+                    .https_or_http()
+                    .enable_http1()
+                    .build();
+                HttpClient::Remote(Client::builder(TokioExecutor::new()).build(connector))
+            }
+        };
+        OctyneClient { transport, http }
+    }
+
+    /// The transport used to reach Octyne.
+    pub fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    /// Sends a request to Octyne, returning the status code and body of the response as-is.
+    pub async fn request(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Full<Bytes>,
+    ) -> Result<(StatusCode, String), ApiError> {
+        self.request_with_headers(method, endpoint, body, &[]).await
+    }
+
+    /// Same as [`OctyneClient::request`], with additional headers sent along with the request.
+    pub async fn request_with_headers(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Full<Bytes>,
+        headers: &[(&str, &str)],
+    ) -> Result<(StatusCode, String), ApiError> {
+        let mut req = Request::builder().method(method);
+        for (key, value) in headers {
+            req = req.header(*key, *value);
+        }
+        let invalid =
+            |e: hyper::http::Error| ApiError::Protocol(format!("Invalid request to Octyne! {}", e));
+        // hyperlocal panics if the endpoint isn't a valid path, so it is checked beforehand.
+        endpoint
+            .parse::<hyper::http::uri::PathAndQuery>()
+            .map_err(|e| invalid(e.into()))?;
+        let response = match (&self.http, &self.transport) {
+            (HttpClient::Unix(client), Transport::Unix(path)) => {
+                let req = req
+                    .uri(Uri::new(path, endpoint))
+                    .body(body)
+                    .map_err(invalid)?;
+                client.request(req).await
+            }
+            (HttpClient::Remote(client), Transport::Remote { url, token }) => {
+                if let Some(token) = token {
+                    req = req.header("Authorization", token);
+                }
+                let req = req
+                    .uri(format!("{}{}", url, endpoint))
+                    .body(body)
+                    .map_err(invalid)?;
+                client.request(req).await
+            }
+            _ => unreachable!("HTTP client does not match transport"),
+        };
+        let (res, body) = super::request::read_str(response).await?;
+        Ok((res.status(), body))
+    }
+
+    /// Sends a request to Octyne, returning the body of the response if it succeeded.
+    pub async fn request_text(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Full<Bytes>,
+    ) -> Result<String, ApiError> {
+        let (status, body) = self.request(method, endpoint, body).await?;
+        check_response(endpoint, status, &body)?;
+        Ok(body)
+    }
+
+    /// Sends a request to Octyne, parsing the JSON body of the response if it succeeded.
+    pub async fn request_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Full<Bytes>,
+    ) -> Result<T, ApiError> {
+        let (status, body) = self.request(method, endpoint, body).await?;
+        parse_response(endpoint, status, &body)
+    }
+
+    /// Sends a request for an action to Octyne, which responds with `{"success": true}` if the
+    /// action was performed, else `failure` is returned as the error.
+    pub async fn request_action(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Full<Bytes>,
+        failure: &str,
+    ) -> Result<(), ApiError> {
+        let json: ActionResponse = self.request_json(method, endpoint, body).await?;
+        if !json.success {
+            return Err(ApiError::Failed(failure.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Connects to a WebSocket endpoint of Octyne, e.g. an app's console, optionally requesting
+    /// the given subprotocol.
+    pub async fn connect_websocket(
+        &self,
+        endpoint: &str,
+        sub_protocol: Option<&str>,
+    ) -> Result<OctyneWebSocket, ApiError> {
+        let (stream, uri, connector): (Box<dyn Stream>, String, Option<Connector>) =
+            match &self.transport {
+                Transport::Unix(path) => {
+                    let stream = UnixStream::connect(path).await.map_err(|e| {
+                        let message = format!("Error connecting to Unix domain socket! {}", e);
+                        ApiError::connection(message, &e)
+                    })?;
+                    let uri = format!("ws://localhost:42069{}", endpoint);
+                    (Box::new(stream), uri, Some(Connector::Plain))
+                }
+                Transport::Remote { url, .. } => {
+                    let parsed = url.parse::<hyper::Uri>().map_err(|e| {
+                        ApiError::Unreachable(format!("Invalid Octyne URL \"{}\"! {}", url, e))
+                    })?;
+                    let tls = parsed.scheme_str() == Some("https");
+                    // IPv6 addresses are in brackets in URLs, e.g. https://[::1]:42069
+                    let host = parsed.host().unwrap_or("localhost");
+                    let host = host.trim_start_matches('[').trim_end_matches(']');
+                    let port = parsed.port_u16().unwrap_or(if tls { 443 } else { 80 });
+                    let stream = TcpStream::connect((host, port)).await.map_err(|e| {
+                        let message = format!("Error connecting to Octyne at {}! {}", url, e);
+                        ApiError::connection(message, &e)
+                    })?;
+                    let uri = format!(
+                        "{}{}",
+                        url.replacen("https://", "wss://", 1)
+                            .replacen("http://", "ws://", 1),
+                        endpoint
+                    );
+                    let connector = match tls {
+                        true => Connector::Rustls(Arc::new(tls_config())),
+                        false => Connector::Plain,
+                    };
+                    (Box::new(stream), uri, Some(connector))
+                }
+            };
+
+        let uri = uri
+            .parse()
+            .map_err(|e| ApiError::Unreachable(format!("Failed to connect to WebSocket! {}", e)))?;
+        let mut req = ClientRequestBuilder::new(uri);
+        if let Some(sub_protocol) = sub_protocol {
+            req = req.with_sub_protocol(sub_protocol);
+        }
+        if let Transport::Remote {
+            token: Some(token), ..
+        } = &self.transport
+        {
+            req = req.with_header("Authorization", token);
+        }
+        let (socket, _) = client_async_tls_with_config(req, stream, None, connector)
+            .await
+            .map_err(|e| match e {
+                tokio_tungstenite::tungstenite::Error::Http(response) => {
+                    let error = response.body().as_ref().map_or(String::new(), |body| {
+                        serde_json::from_slice(body.as_slice())
+                            .map(|json: ErrorResponse| json.error)
+                            .unwrap_or_default()
+                    });
+                    response_error(endpoint, response.status(), error)
+                }
+                tokio_tungstenite::tungstenite::Error::Io(e) => {
+                    let message = format!("Failed to connect to WebSocket! {}", e);
+                    ApiError::connection(message, &e)
+                }
+                e => ApiError::Protocol(format!("Failed to connect to WebSocket! {}", e)),
+            })?;
+        Ok(socket)
+    }
+}
+
+/// Percent-encodes a component of an endpoint, e.g. an app name in `/server/{name}`.
+pub fn encode_component(component: &str) -> String {
+    utf8_percent_encode(component, COMPONENT).to_string()
+}
+
+// Octyne reports errors as `{"error": "..."}` with a non-200 status code, for every endpoint.
+fn check_response(endpoint: &str, status: StatusCode, body: &str) -> Result<(), ApiError> {
+    let error = serde_json::from_str::<ErrorResponse>(body.trim())
+        .map(|json| json.error)
+        .unwrap_or_default();
+    if status != StatusCode::OK || !error.is_empty() {
+        return Err(response_error(endpoint, status, error));
+    }
+    Ok(())
+}
+
+pub(super) fn parse_response<T: DeserializeOwned>(
+    endpoint: &str,
+    status: StatusCode,
+    body: &str,
+) -> Result<T, ApiError> {
+    check_response(endpoint, status, body)?;
+    serde_json::from_str(body.trim()).map_err(ApiError::corrupt)
+}
+
+fn response_error(endpoint: &str, status: StatusCode, error: String) -> ApiError {
+    // Octyne responds with 404 Not Found if the app doesn't exist.
+    if status == StatusCode::NOT_FOUND && endpoint.starts_with("/server/") {
+        let error = match error.is_empty() {
+            true => "This server does not exist!".to_owned(),
+            false => error,
+        };
+        return ApiError::AppNotFound(error);
+    }
+    ApiError::from_status(status, error)
+}
+
+fn tls_config() -> ClientConfig {
+    let root_store = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+    };
+    ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("TLS protocol versions")
+        .with_root_certificates(root_store)
+        .with_no_client_auth()
+}
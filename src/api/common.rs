@@ -1,15 +1,125 @@
+//! Types shared across the API, including the [`ApiError`] returned by every request.
+
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
-pub struct ActionResponse {
-    #[serde(default)]
-    pub success: bool,
+pub(crate) struct ActionResponse {
     #[serde(default)]
-    pub error: String,
+    pub(crate) success: bool,
 }
 
 #[derive(Deserialize, Debug)]
-pub struct ErrorResponse {
+pub(crate) struct ErrorResponse {
     #[serde(default)]
-    pub error: String,
+    pub(crate) error: String,
+}
+
+/// Errors returned by the Octyne API, grouped by category so that scripts can tell them apart
+/// using octynectl's exit code (see [`ApiError::exit_code`]).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    /// The Unix socket of the Octyne instance does not exist.
+    SocketNotFound(String),
+    /// The current user does not have access to the Unix socket of the Octyne instance.
+    PermissionDenied(String),
+    /// The Octyne instance could not be reached (e.g. connection refused, DNS failure).
+    Unreachable(String),
+    /// The requested app does not exist.
+    AppNotFound(String),
+    /// Octyne rejected the credentials or token used.
+    AuthFailed(String),
+    /// Octyne sent a response that could not be understood.
+    Protocol(String),
+    /// Octyne understood the request, but returned an error or failed to perform it.
+    Failed(String),
+    /// An app did not reach the expected state in time (see [`wait_for_status`](super::server::wait_for_status)).
+    Timeout(String),
+}
+
+// Exit codes used by octynectl. These are documented in the README, and must not be changed,
+// since scripts rely on them. Errors not originating from Octyne (e.g. incorrect usage) use 1.
+
+/// Exit code for [`ApiError::Failed`].
+pub const EXIT_FAILED: i32 = 2;
+/// Exit code for [`ApiError::SocketNotFound`].
+pub const EXIT_SOCKET_NOT_FOUND: i32 = 3;
+/// Exit code for [`ApiError::PermissionDenied`].
+pub const EXIT_PERMISSION_DENIED: i32 = 4;
+/// Exit code for [`ApiError::Unreachable`].
+pub const EXIT_UNREACHABLE: i32 = 5;
+/// Exit code for [`ApiError::AppNotFound`].
+pub const EXIT_APP_NOT_FOUND: i32 = 6;
+/// Exit code for [`ApiError::AuthFailed`].
+pub const EXIT_AUTH_FAILED: i32 = 7;
+/// Exit code for [`ApiError::Protocol`].
+pub const EXIT_PROTOCOL: i32 = 8;
+/// Exit code for [`ApiError::Timeout`].
+pub const EXIT_TIMEOUT: i32 = 9;
+
+impl ApiError {
+    /// Stable exit code for this category of error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ApiError::Failed(_) => EXIT_FAILED,
+            ApiError::SocketNotFound(_) => EXIT_SOCKET_NOT_FOUND,
+            ApiError::PermissionDenied(_) => EXIT_PERMISSION_DENIED,
+            ApiError::Unreachable(_) => EXIT_UNREACHABLE,
+            ApiError::AppNotFound(_) => EXIT_APP_NOT_FOUND,
+            ApiError::AuthFailed(_) => EXIT_AUTH_FAILED,
+            ApiError::Protocol(_) => EXIT_PROTOCOL,
+            ApiError::Timeout(_) => EXIT_TIMEOUT,
+        }
+    }
+
+    /// Error for a response from Octyne with a non-200 status code, given its error message.
+    pub fn from_status(status: hyper::StatusCode, error: String) -> ApiError {
+        let error = match error.is_empty() {
+            true => format!("Received status code {} from Octyne!", status.as_str()),
+            false => error,
+        };
+        match status {
+            hyper::StatusCode::UNAUTHORIZED | hyper::StatusCode::FORBIDDEN => {
+                ApiError::AuthFailed(error)
+            }
+            _ => ApiError::Failed(error),
+        }
+    }
+
+    /// Error for a response from Octyne which could not be parsed.
+    pub fn corrupt(e: impl std::fmt::Display) -> ApiError {
+        ApiError::Protocol(format!("Received corrupt response from Octyne! {}", e))
+    }
+
+    /// Error for a failed connection to Octyne, categorised using the underlying I/O error.
+    pub fn connection(message: String, e: &(dyn std::error::Error + 'static)) -> ApiError {
+        let mut source = Some(e);
+        while let Some(e) = source {
+            if let Some(io_error) = e.downcast_ref::<std::io::Error>() {
+                return match io_error.kind() {
+                    std::io::ErrorKind::NotFound => ApiError::SocketNotFound(message),
+                    std::io::ErrorKind::PermissionDenied => ApiError::PermissionDenied(message),
+                    _ => ApiError::Unreachable(message),
+                };
+            }
+            source = e.source();
+        }
+        ApiError::Unreachable(message)
+    }
 }
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ApiError::SocketNotFound(message)
+            | ApiError::PermissionDenied(message)
+            | ApiError::Unreachable(message)
+            | ApiError::AppNotFound(message)
+            | ApiError::AuthFailed(message)
+            | ApiError::Protocol(message)
+            | ApiError::Failed(message)
+            | ApiError::Timeout(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
@@ -1,84 +1,38 @@
-use http_body_util::Full;
-use hyper::{body::Bytes, Method, Request};
-use hyper_util::client::legacy::Client;
-use hyperlocal_with_windows::{UnixClientExt, UnixConnector, Uri};
-
-use crate::utils::misc;
+//! Viewing, editing and reloading the config of Octyne.
 
-use super::common::{ActionResponse, ErrorResponse};
-
-pub async fn get_config() -> Result<String, String> {
-    let url = Uri::new(misc::default_octyne_path(), "/config").into();
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let response = client.get(url).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
+use http_body_util::Full;
+use hyper::Method;
 
-    let json: ErrorResponse = serde_json::from_str(body.trim()).unwrap_or(ErrorResponse {
-        error: "".to_string(),
-    });
+use super::client::OctyneClient;
+use super::common::ApiError;
 
-    if !json.error.is_empty() {
-        return Err(json.error);
-    } else if res.status() != 200 {
-        let default = format!(
-            "Error: Received status code {} from Octyne!",
-            res.status().as_str()
-        );
-        return Err(default);
-    }
-    Ok(body)
+/// Gets the contents of Octyne's config file.
+pub async fn get_config(client: &OctyneClient) -> Result<String, ApiError> {
+    client
+        .request_text(Method::GET, "/config", Full::default())
+        .await
 }
 
-pub async fn get_config_reload() -> Result<(), String> {
-    let url = Uri::new(misc::default_octyne_path(), "/config/reload").into();
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let response = client.get(url).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: ActionResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => return Err(format!("Received corrupt response from Octyne! {}", e)),
-    };
-
-    if !json.error.is_empty() {
-        return Err(json.error);
-    } else if res.status() != 200 {
-        let default = format!(
-            "Error: Received status code {} from Octyne!",
-            res.status().as_str()
-        );
-        return Err(default);
-    } else if !json.success {
-        return Err("Octyne failed to reload the config!".to_owned());
-    }
-    Ok(())
+/// Reloads Octyne's config from disk.
+pub async fn get_config_reload(client: &OctyneClient) -> Result<(), ApiError> {
+    client
+        .request_action(
+            Method::GET,
+            "/config/reload",
+            Full::default(),
+            "Octyne failed to reload the config!",
+        )
+        .await
 }
 
-pub async fn patch_config(new_config: String) -> Result<(), String> {
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let req = Request::builder()
-        .method(Method::PATCH)
-        .uri(Uri::new(misc::default_octyne_path(), "/config"))
-        .body(Full::from(new_config))
-        .expect("Failed to build request!");
-    let response = client.request(req).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: ActionResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => return Err(format!("Received corrupt response from Octyne! {}", e)),
-    };
-
-    if !json.error.is_empty() {
-        return Err(json.error);
-    } else if res.status() != 200 {
-        let default = format!(
-            "Error: Received status code {} from Octyne!",
-            res.status().as_str()
-        );
-        return Err(default);
-    } else if !json.success {
-        return Err("Octyne failed to load the new config!".to_owned());
-    }
-    Ok(())
+/// Replaces Octyne's config with `new_config`, and reloads it.
+pub async fn patch_config(client: &OctyneClient, new_config: String) -> Result<(), ApiError> {
+    client
+        .request_action(
+            Method::PATCH,
+            "/config",
+            Full::from(new_config),
+            "Octyne failed to load the new config!",
+        )
+        .await
 }
@@ -1,6 +1,18 @@
+//! Async client for the Octyne API.
+//!
+//! All requests go through an [`OctyneClient`](client::OctyneClient), which is created from a
+//! [`Transport`](transport::Transport) describing how to reach the Octyne instance. The functions
+//! in each module take the client as their first argument.
+
 pub mod accounts;
+pub mod auth;
+pub mod client;
 pub mod common;
 pub mod config;
+mod request;
 pub mod server;
 pub mod servers;
+pub mod transport;
+#[cfg(target_family = "windows")]
+mod unix_stream_windows;
 pub mod version;
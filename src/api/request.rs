@@ -2,18 +2,28 @@ use http_body_util::BodyExt;
 use hyper::{body::Incoming, Response};
 use hyper_util::client::legacy::Error;
 
+use super::common::ApiError;
+
 pub async fn read_data(
     response: Result<Response<Incoming>, Error>,
-) -> Result<(Response<Incoming>, Vec<u8>), String> {
+) -> Result<(Response<Incoming>, Vec<u8>), ApiError> {
     let mut response = match response {
         Ok(res) => res,
-        Err(e) => return Err(format!("Failed to read response from Octyne! {}", e)),
+        Err(e) => {
+            let message = format!("Failed to read response from Octyne! {}", e);
+            return Err(ApiError::connection(message, &e));
+        }
     };
     let mut bytes: Vec<u8> = Vec::new();
     while let Some(next) = response.frame().await {
         let frame = match next {
             Ok(res) => res,
-            Err(e) => return Err(format!("Failed to read response from Octyne! {}", e)),
+            Err(e) => {
+                return Err(ApiError::Protocol(format!(
+                    "Failed to read response from Octyne! {}",
+                    e
+                )))
+            }
         };
         if let Some(chunk) = frame.data_ref() {
             bytes.extend_from_slice(chunk);
@@ -24,11 +34,11 @@ pub async fn read_data(
 
 pub async fn read_str(
     response: Result<Response<Incoming>, Error>,
-) -> Result<(Response<Incoming>, String), String> {
+) -> Result<(Response<Incoming>, String), ApiError> {
     match read_data(response).await {
         Ok((res, bytes)) => match String::from_utf8(bytes) {
             Ok(parsed) => Ok((res, parsed)),
-            Err(e) => Err(format!("Received corrupt response from Octyne! {}", e)),
+            Err(e) => Err(ApiError::corrupt(e)),
         },
         Err(e) => Err(e),
     }
@@ -1,21 +1,24 @@
-use crate::utils::misc;
-#[cfg(target_family = "windows")]
-use crate::utils::unix_stream_windows::TokioCompatUnixStream as UnixStream;
+//! Controlling a single app managed by Octyne, and connecting to its console.
+
+use std::time::Duration;
+
 use http_body_util::Full;
-use hyper::{body::Bytes, Method, Request};
-use hyper_util::client::legacy::Client;
-use hyperlocal_with_windows::{UnixClientExt, UnixConnector, Uri};
+use hyper::Method;
 use serde::{Deserialize, Serialize};
-#[cfg(target_family = "unix")]
-use tokio::net::UnixStream;
-use tokio_tungstenite::{client_async, tungstenite::ClientRequestBuilder, WebSocketStream};
+use tokio::time::{sleep, Instant};
 
-use super::common::{ActionResponse, ErrorResponse};
+use super::client::{encode_component, OctyneClient};
+use super::common::ApiError;
+use super::transport::OctyneWebSocket;
 
+/// Actions that can be performed on an app with [`post_server`].
 #[derive(Clone, Debug)]
 pub enum PostServerAction {
+    /// Starts the app.
     Start,
+    /// Kills the app.
     Stop,
+    /// Gracefully stops the app.
     Term,
 }
 
@@ -25,144 +28,139 @@ impl std::fmt::Display for PostServerAction {
     }
 }
 
-pub async fn post_server(server_name: String, action: PostServerAction) -> Result<(), String> {
-    let endpoint = format!("/server/{}", server_name);
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let req = Request::builder()
-        .method(Method::POST)
-        .uri(Uri::new(misc::default_octyne_path(), endpoint.as_str()))
-        .body(Full::from(action.to_string().to_uppercase()))
-        .expect("request builder");
-    let response = client.request(req).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: ActionResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(format!("Received corrupt response from Octyne! {}", e));
-        }
-    };
-
-    if res.status() != 200 && json.error.is_empty() {
-        return Err(format!(
-            "Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
-    } else if !json.error.is_empty() {
-        return Err(json.error);
-    } else if !json.success {
-        return Err(format!(
-            "Octyne failed to {} the app!",
-            action.to_string().to_lowercase()
-        ));
-    }
-    Ok(())
+/// Performs an action on an app, e.g. starting or stopping it.
+pub async fn post_server(
+    client: &OctyneClient,
+    server_name: String,
+    action: PostServerAction,
+) -> Result<(), ApiError> {
+    let endpoint = format!("/server/{}", encode_component(&server_name));
+    let body = Full::from(action.to_string().to_uppercase());
+    let failure = format!(
+        "Octyne failed to {} the app!",
+        action.to_string().to_lowercase()
+    );
+    client
+        .request_action(Method::POST, &endpoint, body, &failure)
+        .await
 }
 
-#[derive(Deserialize, Debug)]
+/// Info about an app, returned by [`get_server`].
+#[derive(Deserialize, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetServerResponse {
-    #[serde(default)]
-    pub error: String,
+    /// 0 if the app is offline, 1 if it is online, 2 if it has crashed.
     #[serde(default)]
     pub status: i32,
+    /// CPU usage of the app in percent.
     #[serde(default)]
     pub cpu_usage: f64,
+    /// Memory used by the app in bytes.
     #[serde(default)]
     pub memory_usage: i64,
+    /// Total memory of the system in bytes.
     #[serde(default)]
     pub total_memory: i64,
+    /// Time the app has been running for, in nanoseconds.
     #[serde(default)]
     pub uptime: i64,
+    /// Whether the app is marked for deletion once it stops.
     #[serde(default)]
     pub to_delete: bool,
 }
 
-pub async fn get_server(server_name: String) -> Result<GetServerResponse, String> {
-    let endpoint = format!("/server/{}", server_name);
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let uri = Uri::new(misc::default_octyne_path(), endpoint.as_str()).into();
-    let response = client.get(uri).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
+/// Gets the status and resource usage of an app.
+pub async fn get_server(
+    client: &OctyneClient,
+    server_name: String,
+) -> Result<GetServerResponse, ApiError> {
+    let endpoint = format!("/server/{}", encode_component(&server_name));
+    client
+        .request_json(Method::GET, &endpoint, Full::default())
+        .await
+}
 
-    let json: GetServerResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(format!("Received corrupt response from Octyne! {}", e));
-        }
-    };
+/// Status of an app which is offline.
+pub const STATUS_OFFLINE: i32 = 0;
+/// Status of an app which is online.
+pub const STATUS_ONLINE: i32 = 1;
+/// Status of an app which has crashed.
+pub const STATUS_CRASHED: i32 = 2;
 
-    if res.status() != 200 && json.error.is_empty() {
-        return Err(format!(
-            "Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
-    } else if !json.error.is_empty() {
-        return Err(json.error);
+/// Polls the status of an app every `poll_interval` until it is one of `statuses`, returning the
+/// final status, or [`ApiError::Timeout`] if this takes longer than `timeout`.
+pub async fn wait_for_status(
+    client: &OctyneClient,
+    server_name: String,
+    statuses: &[i32],
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<GetServerResponse, ApiError> {
+    // There is no deadline if the timeout is too long to represent as an instant.
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+        let response = get_server(client, server_name.clone()).await?;
+        if statuses.contains(&response.status) {
+            return Ok(response);
+        } else if deadline.is_some_and(|deadline| Instant::now() + poll_interval > deadline) {
+            return Err(ApiError::Timeout(format!(
+                "Timed out after {}s waiting for {}!",
+                timeout.as_secs_f64(),
+                server_name
+            )));
+        }
+        sleep(poll_interval).await;
     }
-    Ok(json)
 }
 
+/// Message sent or received over the console WebSocket of an app (in console-v2).
 #[derive(Deserialize, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConsoleMessage {
+    /// Type of the message, e.g. `input`, `output`, `error`, `ping`, `pong` or `settings`.
     #[serde(default)]
     pub r#type: String,
     #[serde(skip_serializing_if = "String::is_empty")]
+    /// Error message, for `error` messages.
     #[serde(default)]
     pub message: String,
     #[serde(skip_serializing_if = "String::is_empty")]
+    /// Console input or output, for `input` and `output` messages.
     #[serde(default)]
     pub data: String,
     #[serde(skip_serializing_if = "String::is_empty")]
+    /// ID used to match a `pong` to its `ping`.
     #[serde(default)]
     pub id: String,
 }
 
+/// Connects to the console of an app using console-v2, falling back to v1 if Octyne doesn't
+/// support v2. Returns whether v2 is in use along with the WebSocket.
 pub async fn connect_to_server_console_v1_fallback(
+    client: &OctyneClient,
     server_name: String,
-) -> Result<(WebSocketStream<UnixStream>, bool), String> {
-    match connect_to_server_console(server_name.clone(), true).await {
+) -> Result<(OctyneWebSocket, bool), ApiError> {
+    match connect_to_server_console(client, server_name.clone(), true).await {
         Ok(socket) => Ok((socket, true)),
         Err(e) => {
-            if e.ends_with("Server sent no subprotocol") {
-                return Ok((connect_to_server_console(server_name, false).await?, false));
+            if e.to_string().ends_with("Server sent no subprotocol") {
+                return Ok((
+                    connect_to_server_console(client, server_name, false).await?,
+                    false,
+                ));
             }
             Err(e)
         }
     }
 }
 
+/// Connects to the console of an app, using console-v2 if `v2` is true.
 pub async fn connect_to_server_console(
+    client: &OctyneClient,
     server_name: String,
     v2: bool,
-) -> Result<WebSocketStream<UnixStream>, String> {
-    // Connect to WebSocket over Unix socket
-    let stream = UnixStream::connect(misc::default_octyne_path())
-        .await
-        .map_err(|e| format!("Error connecting to Unix domain socket! {}", e))?;
-
-    let uri = format!("ws://localhost:42069/server/{}/console", server_name)
-        .parse()
-        .map_err(|e| format!("Error connecting to Unix domain socket! {}", e))?;
-    let mut req = ClientRequestBuilder::new(uri);
-    if v2 {
-        req = req.with_sub_protocol("console-v2");
-    }
-    let (socket, _) = client_async(req, stream).await.map_err(|e| {
-        if let tokio_tungstenite::tungstenite::Error::Http(response) = e {
-            response.body().as_ref().map_or(
-                format!("Failed to connect to WebSocket! {}", response.status()),
-                |body| {
-                    serde_json::from_slice(body.as_slice())
-                        .map(|json: ErrorResponse| json.error)
-                        .unwrap_or(response.status().to_string())
-                },
-            )
-        } else {
-            format!("Failed to connect to WebSocket! {}", e)
-        }
-    })?;
-
-    Ok(socket)
+) -> Result<OctyneWebSocket, ApiError> {
+    let endpoint = format!("/server/{}/console", encode_component(&server_name));
+    let sub_protocol = if v2 { Some("console-v2") } else { None };
+    client.connect_websocket(&endpoint, sub_protocol).await
 }
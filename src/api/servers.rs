@@ -1,53 +1,61 @@
+//! Listing the apps managed by Octyne.
+
 use http_body_util::Full;
-use hyper::body::Bytes;
-use hyper_util::client::legacy::Client;
-use hyperlocal_with_windows::{UnixClientExt, UnixConnector, Uri};
+use hyper::Method;
 use serde::Deserialize;
 use serde_json::{Map, Value};
 
-use crate::utils::misc;
+use super::client::OctyneClient;
+use super::common::ApiError;
 
 #[derive(Deserialize, Debug)]
 struct Response {
     #[serde(default)]
     servers: Map<String, Value>,
-    #[serde(default)]
-    error: String,
 }
 
+/// Info about an app, returned by [`get_servers`] when extra info is requested.
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerExtraInfo {
+    /// 0 if the app is offline, 1 if it is online, 2 if it has crashed, -1 if unknown.
     #[serde(default)]
     pub status: i64,
+    /// Whether the app is marked for deletion once it stops.
     #[serde(default)]
     pub to_delete: bool,
 }
 
-pub async fn get_servers(extra_info: bool) -> Result<Map<String, Value>, String> {
+impl ServerExtraInfo {
+    /// Parses an entry of [`get_servers`], which is just the status on older Octyne versions.
+    pub fn from_value(value: Value) -> ServerExtraInfo {
+        match value.as_i64() {
+            Some(status) => ServerExtraInfo {
+                status,
+                to_delete: false,
+            },
+            None => serde_json::from_value(value).unwrap_or(ServerExtraInfo {
+                status: -1,
+                to_delete: false,
+            }),
+        }
+    }
+}
+
+/// Lists the apps managed by Octyne, mapped to their status, or to their [`ServerExtraInfo`] if
+/// `extra_info` is true (use [`ServerExtraInfo::from_value`] to parse either).
+pub async fn get_servers(
+    client: &OctyneClient,
+    extra_info: bool,
+) -> Result<Map<String, Value>, ApiError> {
     let endpoint = if extra_info {
         "/servers?extrainfo=true"
     } else {
         "/servers"
     };
-    let url = Uri::new(misc::default_octyne_path(), endpoint).into();
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let response = client.get(url).await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
-
-    let json: Response = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => return Err(format!("Received corrupt response from Octyne! {}", e)),
-    };
-
-    if !json.error.is_empty() {
-        return Err(json.error);
-    } else if res.status() != 200 {
-        return Err(format!(
-            "Error: Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
-    }
+    let json: Response = client
+        .request_json(Method::GET, endpoint, Full::default())
+        .await?;
     Ok(json.servers)
 }
 
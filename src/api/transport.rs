@@ -0,0 +1,65 @@
+//! Describing how to reach an Octyne instance.
+
+use std::path::PathBuf;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// A stream over which a WebSocket connection to Octyne can be made.
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// WebSocket connection to Octyne, over either a Unix socket or TCP (with or without TLS).
+pub type OctyneWebSocket = WebSocketStream<MaybeTlsStream<Box<dyn Stream>>>;
+
+/// How to reach an Octyne instance.
+#[derive(Clone, Debug)]
+pub enum Transport {
+    /// Local Octyne instance, reached over its Unix socket API.
+    Unix(PathBuf),
+    /// Remote Octyne instance, reached over its HTTP(S) API, authenticated with a token.
+    Remote {
+        /// Base URL of the Octyne instance, without a trailing slash.
+        url: String,
+        /// Token obtained by logging in, if any.
+        token: Option<String>,
+    },
+}
+
+impl Transport {
+    /// Creates a remote transport, validating that the URL is http(s):// with a host.
+    pub fn remote(url: &str, token: Option<String>) -> Result<Transport, String> {
+        let url = url.trim_end_matches('/').to_string();
+        let uri = url
+            .parse::<hyper::Uri>()
+            .map_err(|e| format!("Invalid Octyne URL \"{}\"! {}", url, e))?;
+        match uri.scheme_str() {
+            Some("http") | Some("https") => {}
+            _ => {
+                return Err(format!(
+                    "Invalid Octyne URL \"{}\"! Must be http(s)://",
+                    url
+                ))
+            }
+        }
+        if uri.host().is_none() {
+            return Err(format!("Invalid Octyne URL \"{}\"! No host specified", url));
+        }
+        Ok(Transport::Remote { url, token })
+    }
+
+    /// Whether this transport is over Octyne's HTTP(S) API.
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Transport::Remote { .. })
+    }
+}
+
+impl std::fmt::Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Transport::Unix(path) => write!(f, "{}", path.display()),
+            Transport::Remote { url, .. } => write!(f, "{}", url),
+        }
+    }
+}
@@ -1,10 +1,11 @@
+//! Retrieving the version of Octyne.
+
 use http_body_util::Full;
-use hyper::body::Bytes;
-use hyper_util::client::legacy::Client;
-use hyperlocal_with_windows::{UnixClientExt, UnixConnector, Uri};
+use hyper::Method;
 use serde::Deserialize;
 
-use crate::utils::misc;
+use super::client::{parse_response, OctyneClient};
+use super::common::ApiError;
 
 #[derive(Deserialize, Debug)]
 struct VersionResponse {
@@ -12,28 +13,14 @@ struct VersionResponse {
     version: String,
 }
 
-pub async fn get_version() -> Result<String, String> {
-    let client: Client<UnixConnector, Full<Bytes>> = Client::unix();
-    let response = client
-        .get(Uri::new(misc::default_octyne_path(), "/").into())
-        .await;
-    let (res, body) = crate::utils::request::read_str(response).await?;
+/// Gets the version of Octyne.
+pub async fn get_version(client: &OctyneClient) -> Result<String, ApiError> {
+    let (status, body) = client.request(Method::GET, "/", Full::default()).await?;
 
+    // Octyne 1.0 doesn't respond with JSON.
     if body == "Hi, octyne is online and listening to this port successfully!" {
         return Ok("1.0.x".to_string());
-    } else if res.status() != 200 {
-        return Err(format!(
-            "Received status code {} from Octyne!",
-            res.status().as_str()
-        ));
     }
-
-    let json: VersionResponse = match serde_json::from_str(body.trim()) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(format!("Received corrupt response from Octyne! {}", e));
-        }
-    };
-
+    let json: VersionResponse = parse_response("/", status, &body)?;
     Ok(json.version)
 }
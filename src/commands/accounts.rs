@@ -1,278 +1,132 @@
-use std::{collections::HashMap, process::exit};
-
-use crate::api::accounts::{delete_account, get_accounts, patch_account, post_account};
-
-pub async fn accounts_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if args.is_empty()
-        && (top_level_opts.contains_key("h")
-            || top_level_opts.contains_key("help")
-            || opts.contains_key("h")
-            || opts.contains_key("help"))
-    {
-        accounts_cmd_help();
-    } else if args.is_empty() {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts")
-        );
-        exit(1);
-    } else if args.len() == 1 {
-        accounts_cmd_help();
-    } else if args[1] == "list" || args[1] == "show" {
-        if top_level_opts.contains_key("h")
-            || top_level_opts.contains_key("help")
-            || opts.contains_key("h")
-            || opts.contains_key("help")
-        {
-            accounts_list_cmd_help();
-            return;
-        } else if args.len() != 2 {
-            println!(
-                "{}",
-                crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts list")
-            );
-            exit(1);
-        }
-
-        let accounts = get_accounts().await.unwrap_or_else(|e| {
-            println!("Error: {}", e);
-            exit(1);
-        });
-
-        if accounts.is_empty() {
-            println!("The local Octyne instance has no accounts!");
-            return;
-        }
-
-        println!("Accounts registered with the local Octyne instance:");
-        for account in accounts {
-            println!("{}", account);
-        }
-    } else if args[1] == "create" || args[1] == "add" {
-        if top_level_opts.contains_key("h")
-            || top_level_opts.contains_key("help")
-            || opts.contains_key("h")
-            || opts.contains_key("help")
-        {
-            accounts_create_cmd_help();
-            return;
-        } else if args.len() != 3 {
-            println!(
-                "{}",
-                crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts create")
-            );
-            exit(1);
-        }
-
-        let pass = match rpassword::prompt_password("New password for account: ") {
-            Ok(pass) => pass,
-            Err(err) => {
-                println!("Error: Failed to read password! {}", err);
-                exit(1);
-            }
-        };
-        match rpassword::prompt_password("Confirm password: ") {
-            Ok(confirm_pass) => {
-                if confirm_pass != pass {
-                    println!("Error: Passwords do not match!");
-                    exit(1);
-                }
-            }
-            Err(err) => {
-                println!("Error: Failed to read password! {}", err);
-                exit(1);
-            }
-        };
-        post_account(args[2].to_owned(), pass)
-            .await
-            .unwrap_or_else(|e| {
-                println!("Error: {}", e);
-                exit(1);
-            });
-        println!("Successfully created account {}", args[2]);
-    } else if args[1] == "rename" {
-        if top_level_opts.contains_key("h")
-            || top_level_opts.contains_key("help")
-            || opts.contains_key("h")
-            || opts.contains_key("help")
-        {
-            accounts_rename_cmd_help();
-            return;
-        } else if args.len() != 4 {
-            println!(
-                "{}",
-                crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts rename")
-            );
-            exit(1);
-        }
-        patch_account(Some(args[2].to_owned()), args[3].to_owned(), "".to_owned())
-            .await
-            .unwrap_or_else(|e| {
-                println!("Error: {}", e);
-                exit(1);
-            });
-        println!("Successfully renamed account {} to {}", args[2], args[3]);
-    } else if args[1] == "delete" || args[1] == "remove" {
-        if top_level_opts.contains_key("h")
-            || top_level_opts.contains_key("help")
-            || opts.contains_key("h")
-            || opts.contains_key("help")
-        {
-            accounts_delete_cmd_help();
-            return;
-        } else if args.len() < 3 {
-            println!(
-                "{}",
-                crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts delete")
-            );
-            exit(1);
-        }
-
-        let mut any_errored = false;
-        for username in args[2..].iter() {
-            match delete_account(username.to_string()).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error deleting account {}: {}", username, e);
-                    any_errored = true;
-                }
-            }
-        }
-        if any_errored {
-            exit(1);
-        } else {
-            println!("Successfully deleted specified accounts!");
-        }
-    } else if args[1] == "passwd" {
-        if top_level_opts.contains_key("h")
-            || top_level_opts.contains_key("help")
-            || opts.contains_key("h")
-            || opts.contains_key("help")
-        {
-            accounts_passwd_cmd_help();
-            return;
-        } else if args.len() != 3 {
-            println!(
-                "{}",
-                crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts passwd")
-            );
-            exit(1);
-        }
-
-        let pass = match rpassword::prompt_password("New password for account: ") {
-            Ok(pass) => pass,
-            Err(err) => {
-                println!("Error: Failed to read password! {}", err);
-                exit(1);
-            }
-        };
-        match rpassword::prompt_password("Confirm password: ") {
-            Ok(confirm_pass) => {
-                if confirm_pass != pass {
-                    println!("Error: Passwords do not match!");
-                    exit(1);
-                }
-            }
-            Err(err) => {
-                println!("Error: Failed to read password! {}", err);
-                exit(1);
-            }
-        };
-        patch_account(None, args[2].to_owned(), pass)
-            .await
-            .unwrap_or_else(|e| {
-                println!("Error: {}", e);
-                exit(1);
-            });
-        println!("Successfully changed password for account {}", args[2]);
-    } else {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "accounts")
-        );
-        exit(1);
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use crate::utils::cli::{ArgKind, Command};
+use crate::utils::config::Node;
+use crate::utils::output::{print_json, Output, Results};
+use octynectl::api::accounts::{delete_account, get_accounts, patch_account, post_account};
+
+pub async fn accounts_list_cmd(_args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let accounts = get_accounts(&node.client)
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+    if output.is_json() {
+        return print_json(&json!({ "success": true, "accounts": accounts }));
     }
-}
-
-pub fn accounts_cmd_help() {
-    println!(
-        "Manage Octyne accounts.
 
-Usage: octynectl accounts [OPTIONS] [SUBCOMMAND]
-
-Aliases: account, users, user
-
-Subcommands:
-    list, show           List all accounts
-    create, add          Create a new account
-    rename               Rename an existing account
-    delete, remove       Delete accounts
-    passwd               Change password of an existing account
+    if accounts.is_empty() {
+        println!("No accounts are registered with {}!", node.description());
+        return;
+    }
 
-Options:
-    -h, --help           Print help information"
-    );
+    println!("Accounts registered with {}:", node.description());
+    for account in accounts {
+        println!("{}", account);
+    }
 }
 
-pub fn accounts_list_cmd_help() {
-    println!(
-        "List all Octyne accounts.
-
-Usage: octynectl accounts list [OPTIONS]
-
-Aliases: show
-
-Options:
-    -h, --help           Print help information"
-    );
+pub async fn accounts_create_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let pass = match rpassword::prompt_password("New password for account: ") {
+        Ok(pass) => pass,
+        Err(err) => output.fail("Error", format!("Failed to read password! {}", err), 1),
+    };
+    match rpassword::prompt_password("Confirm password: ") {
+        Ok(confirm_pass) => {
+            if confirm_pass != pass {
+                output.fail("Error", "Passwords do not match!", 1);
+            }
+        }
+        Err(err) => output.fail("Error", format!("Failed to read password! {}", err), 1),
+    };
+    post_account(&node.client, args[0].to_owned(), pass)
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+    output.success(format!("Successfully created account {}", args[0]));
 }
 
-pub fn accounts_create_cmd_help() {
-    println!(
-        "Create a new Octyne account. You will be prompted for a password.
-
-Usage: octynectl accounts create [OPTIONS] [USERNAME]
-
-Aliases: add
-
-Options:
-    -h, --help           Print help information"
-    );
+pub async fn accounts_rename_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    patch_account(
+        &node.client,
+        Some(args[0].to_owned()),
+        args[1].to_owned(),
+        "".to_owned(),
+    )
+    .await
+    .unwrap_or_else(|e| output.fail_api("Error", e));
+    output.success(format!(
+        "Successfully renamed account {} to {}",
+        args[0], args[1]
+    ));
 }
 
-pub fn accounts_rename_cmd_help() {
-    println!(
-        "Rename an existing Octyne account.
-
-Usage: octynectl accounts rename [OPTIONS] [OLD USERNAME] [NEW USERNAME]
-
-Options:
-    -h, --help           Print help information"
-    );
+pub async fn accounts_delete_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let mut results = Results::new(Output::from_opts(&opts));
+    for username in args.iter() {
+        match delete_account(&node.client, username.to_string()).await {
+            Ok(_) => results.success(username),
+            Err(e) => {
+                let context = format!("Error deleting account {}", username);
+                results.failure_api(username, &context, e);
+            }
+        }
+    }
+    results.finish(Some("Successfully deleted specified accounts!"));
 }
 
-pub fn accounts_delete_cmd_help() {
-    println!(
-        "Delete Octyne accounts.
-
-Usage: octynectl accounts delete [OPTIONS] [USERNAMES...]
-
-Aliases: remove
-
-Options:
-    -h, --help           Print help information"
-    );
+pub async fn accounts_passwd_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let pass = match rpassword::prompt_password("New password for account: ") {
+        Ok(pass) => pass,
+        Err(err) => output.fail("Error", format!("Failed to read password! {}", err), 1),
+    };
+    match rpassword::prompt_password("Confirm password: ") {
+        Ok(confirm_pass) => {
+            if confirm_pass != pass {
+                output.fail("Error", "Passwords do not match!", 1);
+            }
+        }
+        Err(err) => output.fail("Error", format!("Failed to read password! {}", err), 1),
+    };
+    patch_account(&node.client, None, args[0].to_owned(), pass)
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+    output.success(format!(
+        "Successfully changed password for account {}",
+        args[0]
+    ));
 }
 
-pub fn accounts_passwd_cmd_help() {
-    println!(
-        "Change the password of an existing Octyne account.
-
-Usage: octynectl accounts passwd [OPTIONS] [USERNAME]
-
-Options:
-    -h, --help           Print help information"
-    );
-}
+pub const COMMAND: Command = Command::new("accounts", "Manage Octyne accounts")
+    .aliases(&["account", "users", "user"])
+    .subcommands(&[
+        LIST_COMMAND,
+        CREATE_COMMAND,
+        RENAME_COMMAND,
+        DELETE_COMMAND,
+        PASSWD_COMMAND,
+    ]);
+
+pub const LIST_COMMAND: Command = Command::new("list", "List all accounts").aliases(&["show"]);
+
+pub const CREATE_COMMAND: Command = Command::new("create", "Create a new account")
+    .description("You will be prompted for a password.")
+    .aliases(&["add"])
+    .args("[USERNAME]", 1, Some(1));
+
+pub const RENAME_COMMAND: Command = Command::new("rename", "Rename an existing account")
+    .args("[OLD USERNAME] [NEW USERNAME]", 2, Some(2))
+    .completes(ArgKind::Accounts);
+
+pub const DELETE_COMMAND: Command = Command::new("delete", "Delete accounts")
+    .aliases(&["remove"])
+    .args("[USERNAMES...]", 1, None)
+    .completes(ArgKind::Accounts);
+
+pub const PASSWD_COMMAND: Command =
+    Command::new("passwd", "Change password of an existing account")
+        .args("[USERNAME]", 1, Some(1))
+        .completes(ArgKind::Accounts);
@@ -0,0 +1,348 @@
+use std::{collections::HashMap, process::exit, time::Duration};
+
+use crate::help::ROOT;
+use crate::utils::cli::{
+    find_option, find_short_option, ArgKind, Command, Opt, OptValue, ValueKind,
+};
+use crate::utils::config::read_config;
+
+pub const SHELLS: [&str; 4] = ["bash", "zsh", "fish", "powershell"];
+
+// Options used to pick the Octyne instance, passed along when completing app/account names.
+const CONNECTION_OPTIONS: [&str; 4] = ["socket", "port", "url", "node"];
+
+pub fn completions_cmd(args: Vec<String>, _opts: HashMap<String, String>) {
+    match args[0].as_str() {
+        "bash" => print!("{}", BASH_SCRIPT),
+        "zsh" => print!("{}", ZSH_SCRIPT),
+        "fish" => print!("{}", FISH_SCRIPT),
+        "powershell" => print!("{}", POWERSHELL_SCRIPT),
+        shell => {
+            println!(
+                "Error: Unsupported shell \"{}\"! (Supported shells: {})",
+                shell,
+                SHELLS.join(",")
+            );
+            exit(1);
+        }
+    }
+}
+
+// Used by the completion scripts, prints one candidate per line (with an optional tab-separated
+// description), or `:files` if the shell should complete file paths instead.
+pub async fn complete_cmd(args: Vec<String>, _opts: HashMap<String, String>) {
+    match complete(args).await {
+        Some(candidates) => {
+            for (candidate, description) in candidates {
+                if description.is_empty() {
+                    println!("{}", candidate);
+                } else {
+                    println!("{}\t{}", candidate, description);
+                }
+            }
+        }
+        None => println!(":files"),
+    }
+}
+
+type Candidates = Option<Vec<(String, String)>>;
+
+// The last word is the one being completed, the rest are the words before it.
+async fn complete(mut words: Vec<String>) -> Candidates {
+    let current = words.pop().unwrap_or_default();
+    let mut path = vec![&ROOT];
+    let mut positional: Vec<String> = Vec::new();
+    let mut opts: HashMap<String, String> = HashMap::new();
+    let mut pending: Option<&Opt> = None;
+    let mut only_positional = false;
+
+    for word in words {
+        let command = path[path.len() - 1];
+        if let Some(opt) = pending.take() {
+            opts.insert(opt.long.to_owned(), word);
+        } else if only_positional || word == "-" || !word.starts_with('-') {
+            if !only_positional && positional.is_empty() && !command.subcommands.is_empty() {
+                match command.find_subcommand(&word) {
+                    Some(subcommand) => path.push(subcommand),
+                    None => return Some(Vec::new()),
+                }
+            } else {
+                positional.push(word);
+            }
+        } else if word == "--" {
+            only_positional = true;
+        } else if let Some(long) = word.strip_prefix("--") {
+            match long.split_once('=') {
+                Some((name, value)) => {
+                    opts.insert(name.to_owned(), value.to_owned());
+                }
+                None => match find_option(&path, long) {
+                    Some(opt) if matches!(opt.value, OptValue::Required(..)) => pending = Some(opt),
+                    _ => {
+                        opts.insert(long.to_owned(), String::new());
+                    }
+                },
+            }
+        } else {
+            let shorts = word[1..].chars().collect::<Vec<char>>();
+            for (index, short) in shorts.iter().enumerate() {
+                match find_short_option(&path, *short) {
+                    Some(opt) if matches!(opt.value, OptValue::Required(..)) => {
+                        if index == shorts.len() - 1 {
+                            pending = Some(opt);
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let command = path[path.len() - 1];
+    let candidates = if let Some(opt) = pending {
+        option_values(opt)?
+    } else if !only_positional && current.starts_with("--") && current.contains('=') {
+        let (name, _) = current[2..].split_once('=').unwrap_or_default();
+        let Some(opt) = find_option(&path, name) else {
+            return Some(Vec::new());
+        };
+        option_values(opt)?
+            .into_iter()
+            .map(|(value, description)| (format!("--{}={}", name, value), description))
+            .collect()
+    } else if !only_positional && current.starts_with('-') {
+        command
+            .options
+            .iter()
+            .chain(ROOT.options.iter())
+            .map(|opt| {
+                let help = opt.help.lines().next().unwrap_or_default().to_owned();
+                (format!("--{}", opt.long), help)
+            })
+            .collect()
+    } else if !command.subcommands.is_empty() && positional.is_empty() {
+        subcommands(command)
+    } else if command.max_args.is_some_and(|max| positional.len() >= max) {
+        Vec::new()
+    } else {
+        arg_values(command, &positional, &opts).await?
+    };
+
+    Some(
+        candidates
+            .into_iter()
+            .filter(|(candidate, _)| candidate.starts_with(&current))
+            .collect(),
+    )
+}
+
+fn subcommands(command: &Command) -> Vec<(String, String)> {
+    command
+        .subcommands
+        .iter()
+        .filter(|cmd| !cmd.hidden)
+        .map(|cmd| (cmd.name.to_owned(), cmd.summary.to_owned()))
+        .collect()
+}
+
+fn node_names() -> Vec<String> {
+    read_config()
+        .map(|config| config.nodes.into_keys().collect())
+        .unwrap_or_default()
+}
+
+fn option_values(opt: &Opt) -> Candidates {
+    match opt.value {
+        OptValue::Required(_, ValueKind::Choice(choices))
+        | OptValue::Optional(_, ValueKind::Choice(choices)) => Some(
+            choices
+                .iter()
+                .map(|choice| (choice.to_string(), String::new()))
+                .collect(),
+        ),
+        OptValue::Required("path", _) => None,
+        _ if opt.long == "node" => Some(
+            node_names()
+                .into_iter()
+                .map(|name| (name, String::new()))
+                .collect(),
+        ),
+        _ => Some(Vec::new()),
+    }
+}
+
+async fn arg_values(
+    command: &Command,
+    positional: &[String],
+    opts: &HashMap<String, String>,
+) -> Candidates {
+    let values = match command.arg_kind {
+        ArgKind::None => Vec::new(),
+        ArgKind::Files => return None,
+        ArgKind::Choice(choices) => choices.iter().map(|choice| choice.to_string()).collect(),
+        ArgKind::Subcommands => {
+            let mut command = &ROOT;
+            for name in positional {
+                match command.find_subcommand(name) {
+                    Some(subcommand) => command = subcommand,
+                    None => return Some(Vec::new()),
+                }
+            }
+            return Some(subcommands(command));
+        }
+        ArgKind::Nodes => node_names(),
+        ArgKind::Apps | ArgKind::Accounts => {
+            let opts = opts
+                .iter()
+                .filter(|(key, _)| CONNECTION_OPTIONS.contains(&key.as_str()))
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect();
+            let Ok(node) = crate::utils::misc::octyne_node(&opts) else {
+                return Some(Vec::new());
+            };
+            let request = async {
+                match command.arg_kind {
+                    ArgKind::Apps => octynectl::api::servers::get_servers(&node.client, false)
+                        .await
+                        .map(|servers| servers.into_iter().map(|(name, _)| name).collect()),
+                    _ => octynectl::api::accounts::get_accounts(&node.client).await,
+                }
+            };
+            // Don't hang the shell if Octyne is unresponsive.
+            match tokio::time::timeout(Duration::from_secs(3), request).await {
+                Ok(Ok(values)) => values,
+                _ => Vec::new(),
+            }
+        }
+    };
+    Some(
+        values
+            .into_iter()
+            .filter(|value| !positional.contains(value))
+            .map(|value| (value, String::new()))
+            .collect(),
+    )
+}
+
+pub const COMMAND: Command = Command::new("completions", "Generate shell completion scripts")
+    .description(
+        "
+Supported shells are bash, zsh, fish and powershell. App and account names are
+completed by querying Octyne, using the --node, --url, --socket or --port option
+if present on the command line.
+
+To enable completions, add the following to your shell's config file:
+
+    bash (~/.bashrc):            source <(octynectl completions bash)
+    zsh (~/.zshrc):              source <(octynectl completions zsh)
+    fish (~/.config/fish/config.fish):
+                                 octynectl completions fish | source
+    powershell ($PROFILE):       octynectl completions powershell | Out-String | Invoke-Expression",
+    )
+    .aliases(&["completion"])
+    .args("[SHELL]", 1, Some(1))
+    .completes(ArgKind::Choice(&SHELLS));
+
+pub const COMPLETE_COMMAND: Command = Command::new(
+    "__complete",
+    "Print completions for a partially typed command",
+)
+.hidden()
+.args("(WORDS...)", 0, None);
+
+const BASH_SCRIPT: &str = r#"# bash completion for octynectl
+
+_octynectl() {
+    local line="${COMP_LINE:0:COMP_POINT}" candidate
+    local -a words candidates=()
+    read -ra words <<< "$line"
+    [[ "$line" =~ [[:space:]]$ ]] && words+=("")
+    local cur="${words[${#words[@]}-1]}"
+
+    while IFS= read -r candidate; do
+        if [[ "$candidate" == ":files" ]]; then
+            compopt -o default
+            COMPREPLY=()
+            return
+        fi
+        [[ -n "$candidate" ]] && candidates+=("${candidate%%$'\t'*}")
+    done < <("${words[0]}" __complete -- "${words[@]:1}" 2>/dev/null)
+
+    COMPREPLY=($(compgen -W "${candidates[*]}" -- "$cur"))
+    # Bash treats `=` as a word separator, so only the part after it should be completed.
+    if [[ "$cur" == *=* && "$COMP_WORDBREAKS" == *=* ]]; then
+        COMPREPLY=("${COMPREPLY[@]#"${cur%"${cur##*=}"}"}")
+    fi
+}
+
+complete -F _octynectl octynectl
+"#;
+
+const ZSH_SCRIPT: &str = r#"#compdef octynectl
+
+_octynectl() {
+    local -a candidates
+    local line name description
+    for line in "${(@f)$("${words[1]}" __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}"; do
+        if [[ "$line" == ":files" ]]; then
+            _files
+            return
+        fi
+        [[ -z "$line" ]] && continue
+        name="${line%%$'\t'*}"
+        description=""
+        [[ "$line" == *$'\t'* ]] && description="${line#*$'\t'}"
+        candidates+=("${name//:/\\:}${description:+:$description}")
+    done
+    _describe -t values 'octynectl' candidates
+}
+
+if [[ "$funcstack[1]" == "_octynectl" ]]; then
+    _octynectl "$@"
+else
+    compdef _octynectl octynectl
+fi
+"#;
+
+const FISH_SCRIPT: &str = r#"# fish completion for octynectl
+
+function __octynectl_complete
+    set -l current (commandline -ct)
+    set -l tokens (commandline -opc) "$current"
+    for line in ($tokens[1] __complete -- $tokens[2..-1] 2>/dev/null)
+        if test "$line" = ":files"
+            __fish_complete_path "$current"
+            return
+        end
+        echo $line
+    end
+end
+
+complete -c octynectl -f -a '(__octynectl_complete)'
+"#;
+
+const POWERSHELL_SCRIPT: &str = r#"# powershell completion for octynectl
+
+Register-ArgumentCompleter -Native -CommandName octynectl -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements |
+        Where-Object { $_.Extent.StartOffset -lt $cursorPosition } |
+        ForEach-Object { $_.ToString() })
+    if ($wordToComplete -eq '') {
+        $words += ''
+    }
+    $arguments = @($words | Select-Object -Skip 1)
+    foreach ($line in (& $words[0] __complete -- @arguments 2>$null)) {
+        if ($line -eq ':files') {
+            return
+        }
+        $candidate, $description = $line -split "`t", 2
+        if (-not $description) {
+            $description = $candidate
+        }
+        [System.Management.Automation.CompletionResult]::new(
+            $candidate, $candidate, 'ParameterValue', $description)
+    }
+}
+"#;
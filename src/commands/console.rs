@@ -4,56 +4,61 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use crate::api::server::{connect_to_server_console_v1_fallback, ConsoleMessage};
-use crossterm::{execute, tty::IsTty};
-use futures_util::{SinkExt, StreamExt};
+use crate::help::invalid_usage;
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::console_ui::{Connection, ConsoleUi, UiAction};
+use crate::utils::history::{add_history, read_history};
+use crate::utils::output::Output;
+use crossterm::{
+    event::{Event, EventStream, KeyEventKind},
+    tty::IsTty,
+};
+use futures_util::{SinkExt, Stream, StreamExt};
+use octynectl::api::common::{ApiError, EXIT_FAILED, EXIT_PROTOCOL};
+use octynectl::api::server::{connect_to_server_console_v1_fallback, ConsoleMessage};
+use octynectl::api::transport::OctyneWebSocket;
+use serde_json::json;
 use tokio::{
     select, signal,
-    time::{interval_at, Instant},
+    time::{interval_at, sleep_until, timeout, Instant},
 };
 use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
 use tokio_tungstenite::tungstenite::protocol::CloseFrame;
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
 use tokio_util::codec::{FramedRead, LinesCodec};
 
-pub async fn console_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        console_cmd_help();
-        return;
-    } else if args.len() != 2 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "console")
-        );
-        exit(1);
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Time after which a reconnection attempt is given up on, to be retried after the backoff.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn console_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let interactive = !opts.contains_key("no-interactive") // --no-interactive is unset
+        && !output.is_json() // --output=json is unset
+        && std::io::stdout().is_tty() // TTY is present
+        && std::io::stdin().is_tty();
+    let reconnect = opts.contains_key("reconnect");
+    if reconnect && !interactive {
+        let error = "--reconnect can only be used with the interactive console (in a terminal)";
+        output.fail_usage(invalid_usage(error, "console"));
     }
 
     // Connect to WebSocket over Unix socket
-    let (socket, v2) = connect_to_server_console_v1_fallback(args[1].clone())
+    let (socket, v2) = connect_to_server_console_v1_fallback(&node.client, args[0].clone())
         .await
-        .unwrap_or_else(|e| {
-            println!("Error: {}", e);
-            exit(1);
-        });
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+    // If interactive, open the full-screen console UI
+    if interactive {
+        return interactive_console(args[0].clone(), socket, v2, reconnect, &node).await;
+    }
     let (mut write, read) = socket.split();
 
     // Create a channel, if reading fails, terminate write thread and exit.
     // CancellationToken may be better?...
     let (tx, mut rx) = tokio::sync::mpsc::channel::<(i32, String)>(1);
 
-    // If interactive, move to alternate screen
-    let interactive = !opts.contains_key("no-interactive") // --no-interactive is unset
-        && std::io::stdout().is_tty(); // TTY is present
-    if interactive {
-        execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen).unwrap();
-    }
-
     // Create read thread
     tokio::spawn(async move {
         let mut read = read;
@@ -61,30 +66,36 @@ pub async fn console_cmd(args: Vec<String>, top_level_opts: HashMap<String, Stri
             let item = match item {
                 Ok(message) => message,
                 Err(e) => {
-                    return tx.send((1, format!("Read error: {}", e))).await.unwrap();
+                    return tx
+                        .send((EXIT_PROTOCOL, format!("Read error: {}", e)))
+                        .await
+                        .unwrap();
                 }
             };
             if item.is_close() {
-                tx.send((1, "Read error: Received close message from Octyne!".into()))
-                    .await
-                    .unwrap();
+                tx.send((
+                    EXIT_PROTOCOL,
+                    "Read error: Received close message from Octyne!".into(),
+                ))
+                .await
+                .unwrap();
                 return;
             }
             match item.to_text() {
                 Ok(item) => {
-                    let output = if v2 {
+                    let data = if v2 {
                         // Parse message
                         let json: ConsoleMessage = match serde_json::from_str(item) {
                             Ok(json) => json,
                             Err(e) => {
-                                println!("Error: Received corrupt message from Octyne! {}", e);
-                                exit(1);
+                                let error = format!("Received corrupt message from Octyne! {}", e);
+                                output.fail("Error", error, EXIT_PROTOCOL);
                             }
                         };
                         if json.r#type == "output" {
                             json.data
                         } else if json.r#type == "error" {
-                            let err = (1, format!("Error: {}", json.message));
+                            let err = (EXIT_FAILED, format!("Error: {}", json.message));
                             return tx.send(err).await.unwrap();
                         } else {
                             continue; // Discard the rest
@@ -92,20 +103,17 @@ pub async fn console_cmd(args: Vec<String>, top_level_opts: HashMap<String, Stri
                     } else {
                         item.to_string()
                     };
-                    /* TODO:
-                    execute!(
-                        std::io::stdout(),
-                        crossterm::cursor::SavePosition,
-                        crossterm::cursor::MoveToColumn(0)
-                    )
-                    .unwrap();
-                    println!();
-                    execute!(std::io::stdout(), crossterm::cursor::MoveUp(1)).unwrap(); */
-                    println!("{}", output.trim());
-                    // execute!(std::io::stdout(), crossterm::cursor::RestorePosition).unwrap();
+                    if output.is_json() {
+                        println!("{}", json!({ "type": "output", "data": data }));
+                    } else {
+                        println!("{}", data.trim());
+                    }
                 }
                 Err(e) => {
-                    return tx.send((1, format!("Read error: {}", e))).await.unwrap();
+                    return tx
+                        .send((EXIT_PROTOCOL, format!("Read error: {}", e)))
+                        .await
+                        .unwrap();
                 }
             };
         }
@@ -129,33 +137,14 @@ pub async fn console_cmd(args: Vec<String>, top_level_opts: HashMap<String, Stri
                 if line.is_empty() {
                     continue;
                 }
-                let message = if v2 {
-                    serde_json::to_string(&ConsoleMessage {
-                        r#type: "input".into(),
-                        data: line.clone(),
-                        message: "".into(),
-                        id: "".into(),
-                    })
-                    .unwrap()
-                } else {
-                    line
-                };
-                match write.send(Message::Text(message.into())).await {
+                match write.send(input_message(line, v2)).await {
                     Ok(()) => {}
                     Err(e) => break exit_reason = (1, format!("Write error: {}", e))
                 }
             }
             _ = interval.tick() => {
                 if v2 {
-                    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-                    let message = serde_json::to_string(&ConsoleMessage {
-                        r#type: "ping".into(),
-                        data: "".into(),
-                        message: "".into(),
-                        id: timestamp.as_millis().to_string(),
-                    })
-                    .unwrap();
-                    match write.send(Message::Text(message.into())).await {
+                    match write.send(ping_message()).await {
                         Ok(()) => {}
                         Err(e) => break exit_reason = (1, format!("Write error: {}", e))
                     }
@@ -167,10 +156,10 @@ pub async fn console_cmd(args: Vec<String>, top_level_opts: HashMap<String, Stri
     }
 
     // Gracefully exit on EOF
-    if interactive {
-        execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen).unwrap();
-    }
-    if exit_reason.0 != 0 {
+    if exit_reason.0 != 0 && output.is_json() {
+        let error = json!({ "success": false, "error": exit_reason.1, "exitCode": exit_reason.0 });
+        println!("{}", error);
+    } else if exit_reason.0 != 0 {
         println!("{}", exit_reason.1);
     }
     write
@@ -195,22 +184,240 @@ pub async fn console_cmd(args: Vec<String>, top_level_opts: HashMap<String, Stri
     exit(exit_reason.0);
 }
 
-pub fn console_cmd_help() {
-    println!(
-        "Interact with an app's console and send input.
+// Full-screen console with the app's output, a status bar and a pinned input line. If the
+// connection is lost, the output is kept on screen until the user quits, or with --reconnect,
+// octynectl reconnects with exponential backoff.
+async fn interactive_console(
+    name: String,
+    socket: OctyneWebSocket,
+    mut v2: bool,
+    reconnect: bool,
+    node: &Node,
+) {
+    let mut ui = ConsoleUi::new(name.clone(), v2, read_history(node, &name));
+    let (mut write, mut read) = socket.split();
+    ConsoleUi::enter();
+    let mut events = EventStream::new();
+    let ping_duration = Duration::from_secs(5);
+    let mut interval = interval_at(Instant::now() + ping_duration, ping_duration);
+    let mut lost: Option<(i32, String)> = None;
+    let mut attempt = 0;
+    let mut retry_at = Instant::now();
+    // Reconnection attempts run alongside input, so the UI stays responsive while connecting.
+    let mut connecting = None;
+    let mut resuming = false;
+    let quit_reason = loop {
+        ui.draw();
+        let connected = lost.is_none();
+        let reason = select! {
+            data = next_output(&mut read, v2), if connected => match data {
+                Ok(Some(data)) if resuming => {
+                    ui.push_logs(&data);
+                    resuming = false;
+                    continue;
+                }
+                Ok(Some(data)) => {
+                    ui.push_output(&data);
+                    continue;
+                }
+                Ok(None) => (0, "Console closed by remote.".to_owned()),
+                Err((error, exit_code)) => (exit_code, format!("Error: {}", error)),
+            },
+            _ = sleep_until(retry_at), if !connected && reconnect && connecting.is_none() => {
+                let attempt = connect_to_server_console_v1_fallback(&node.client, name.clone());
+                connecting = Some(Box::pin(timeout(CONNECT_TIMEOUT, attempt)));
+                continue;
+            }
+            result = async { connecting.as_mut().unwrap().await }, if connecting.is_some() => {
+                connecting = None;
+                match result {
+                    Ok(Ok((socket, new_v2))) => {
+                        (write, read) = socket.split();
+                        (v2, ui.v2) = (new_v2, new_v2);
+                        ui.connection = Connection::Connected;
+                        ui.push_notice("Reconnected.");
+                        lost = None;
+                        resuming = true;
+                    }
+                    Ok(Err(_)) | Err(_) => {
+                        attempt += 1;
+                        ui.connection = Connection::Reconnecting(attempt);
+                        retry_at = Instant::now() + backoff(attempt);
+                    }
+                }
+                continue;
+            }
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind != KeyEventKind::Release => {
+                    match ui.handle_key(key) {
+                        Some(UiAction::Send(line)) => {
+                            add_history(node, &name, &line).unwrap_or_default();
+                            if !connected {
+                                ui.push_notice("Not connected, the input was not sent.");
+                                continue;
+                            }
+                            match write.send(input_message(line, v2)).await {
+                                Ok(()) => continue,
+                                Err(e) => (1, format!("Write error: {}", e)),
+                            }
+                        }
+                        Some(UiAction::Quit) => break (0, String::new()),
+                        None => continue,
+                    }
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => break (1, format!("Error: Failed to read input! {}", e)),
+                None => break (0, String::new()),
+            },
+            _ = interval.tick(), if v2 && connected => match write.send(ping_message()).await {
+                Ok(()) => continue,
+                Err(e) => (1, format!("Write error: {}", e)),
+            },
+        };
+
+        // The connection was lost.
+        if reconnect {
+            attempt = 1;
+            ui.push_notice(&format!("{} Reconnecting…", reason.1));
+            ui.connection = Connection::Reconnecting(attempt);
+            retry_at = Instant::now() + backoff(attempt);
+        } else {
+            ui.push_notice(&format!("{} Press Ctrl-C to exit.", reason.1));
+            ui.connection = Connection::Disconnected;
+        }
+        lost = Some(reason);
+    };
+    ConsoleUi::leave();
+
+    let connected = lost.is_none();
+    let (exit_code, reason) = lost.unwrap_or(quit_reason);
+    if !reason.is_empty() {
+        println!("{}", reason);
+    }
+    if connected && exit_code == 0 {
+        let mut socket = read.reunite(write).unwrap();
+        close_console(&mut socket).await.unwrap_or_else(|e| {
+            println!("Close error: {}", e);
+            exit(1);
+        });
+    }
+    exit(exit_code);
+}
+
+// Delay before reconnection attempts, doubling each time from 1 second up to 30 seconds.
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.saturating_sub(1).min(5)).min(Duration::from_secs(30))
+}
+
+pub fn ping_message() -> Message {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let message = ConsoleMessage {
+        r#type: "ping".into(),
+        data: "".into(),
+        message: "".into(),
+        id: timestamp.as_millis().to_string(),
+    };
+    Message::Text(serde_json::to_string(&message).unwrap().into())
+}
+
+// Message sending a line of input to the console, in console-v2 or v1.
+pub fn input_message(line: String, v2: bool) -> Message {
+    if !v2 {
+        return Message::Text(line.into());
+    }
+    let message = ConsoleMessage {
+        r#type: "input".into(),
+        data: line,
+        message: "".into(),
+        id: "".into(),
+    };
+    Message::Text(serde_json::to_string(&message).unwrap().into())
+}
+
+// Reads messages from the console until the next output from the app, returning None once the
+// connection is closed. Errors come with the exit code to use.
+pub async fn next_output<S>(socket: &mut S, v2: bool) -> Result<Option<String>, (String, i32)>
+where
+    S: Stream<Item = Result<Message, WsError>> + Unpin,
+{
+    while let Some(item) = socket.next().await {
+        let item = item.map_err(|e| (format!("Read error: {}", e), EXIT_PROTOCOL))?;
+        if item.is_close() {
+            return Ok(None);
+        } else if !item.is_text() {
+            continue;
+        }
+        let item = item
+            .to_text()
+            .map_err(|e| (format!("Read error: {}", e), EXIT_PROTOCOL))?;
+        if !v2 {
+            return Ok(Some(item.to_owned()));
+        }
+        let json: ConsoleMessage = serde_json::from_str(item).map_err(|e| {
+            let error = format!("Received corrupt message from Octyne! {}", e);
+            (error, EXIT_PROTOCOL)
+        })?;
+        if json.r#type == "output" {
+            return Ok(Some(json.data));
+        } else if json.r#type == "error" {
+            return Err((json.message, EXIT_FAILED));
+        } // Discard the rest
+    }
+    Ok(None)
+}
+
+// Closes the console, waiting for Octyne to close the connection too, else it may be dropped before
+// Octyne reads input sent just before.
+pub async fn close_console(socket: &mut OctyneWebSocket) -> Result<(), ApiError> {
+    socket
+        .close(Some(CloseFrame {
+            code: CloseCode::Normal,
+            reason: "Done".into(),
+        }))
+        .await
+        .map_err(|e| ApiError::Protocol(format!("Failed to close WebSocket! {}", e)))?;
+    let drain = async { while let Some(Ok(_)) = socket.next().await {} };
+    timeout(CLOSE_TIMEOUT, drain).await.unwrap_or_default();
+    Ok(())
+}
 
+pub const COMMAND: Command =
+    Command::new("console", "Interact with an app's console and send input")
+        .description(
+            "
 This opens an interactive terminal UI, which can be disabled using the
 `--no-interactive` flag. The interactive UI will be disabled automatically if
 the command output is not being sent to a TTY (terminal) session.
 
-If you only want the app's output logs, and don't want to send any input to it,
-use the `logs` command instead.
+The UI shows the app's output above a status bar and an input line. Use
+PageUp/PageDown to scroll through the output, and Ctrl-C to exit.
 
-Usage: octynectl console [OPTIONS] [APP NAME]
+The input line supports the usual readline keybindings e.g. Ctrl-A/Ctrl-E,
+Alt-B/Alt-F (or Ctrl-Left/Ctrl-Right) to move by word, Ctrl-W/Ctrl-K/Ctrl-U to
+delete text and Ctrl-Y to paste it back. Input history is saved for each app
+(separately for each node), and can be browsed with Up/Down or searched with
+Ctrl-R.
 
-Options:
-    -h, --help               Print help information
-    --no-interactive         Don't setup an interactive console for an end user,
-                             just accept stdin and log output to stdout"
-    );
-}
+If the connection to the app is lost, the console stays open so the output can
+still be read. With --reconnect, octynectl keeps trying to reconnect instead.
+
+With --output=json, the console is never interactive, and each output from the
+app is printed as a JSON object on its own line.
+
+If you only want the app's output logs, and don't want to send any input to it,
+use the `logs` command instead.",
+        )
+        .args("[APP NAME]", 1, Some(1))
+        .completes(ArgKind::Apps)
+        .options(&[
+            Opt::flag(
+                "no-interactive",
+                "Don't setup an interactive console for an end user,
+just accept stdin and log output to stdout",
+            ),
+            Opt::flag(
+                "reconnect",
+                "Keep the interactive console open and reconnect
+if the connection is lost (e.g. when Octyne reloads)",
+            ),
+        ]);
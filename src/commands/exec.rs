@@ -0,0 +1,133 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures_util::SinkExt;
+use regex::Regex;
+use serde_json::json;
+use tokio::time::timeout_at;
+
+use super::console::{close_console, input_message, next_output};
+use super::wait::{timeout, TIMEOUT_OPTION};
+use crate::help::invalid_usage;
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::misc::{deadline, parse_duration};
+use crate::utils::output::{print_json, Output};
+use octynectl::api::common::{EXIT_PROTOCOL, EXIT_TIMEOUT};
+use octynectl::api::server::connect_to_server_console_v1_fallback;
+
+// Time to wait for the logs Octyne sends on connecting, before sending the command.
+const LOGS_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn exec_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    if opts.contains_key("capture") && opts.contains_key("until") {
+        let error = "--capture and --until cannot be used together";
+        output.fail_usage(invalid_usage(error, "exec"));
+    } else if opts.contains_key("timeout") && !opts.contains_key("until") {
+        let error = "--timeout can only be used with --until";
+        output.fail_usage(invalid_usage(error, "exec"));
+    }
+    let until = opts.get("until").map(|until| {
+        Regex::new(until)
+            .unwrap_or_else(|e| output.fail("Error", format!("Invalid regex! {}", e), 1))
+    });
+    let capture = match opts.get("capture") {
+        Some(capture) => parse_duration(capture).ok(),
+        None if until.is_some() => Some(timeout(&opts)),
+        None => None,
+    };
+    let input = args[1..].join(" ");
+
+    let (mut socket, v2) = connect_to_server_console_v1_fallback(&node.client, args[0].clone())
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+
+    // Octyne sends the logs so far on connecting, which shouldn't be captured as the command output.
+    match tokio::time::timeout(LOGS_TIMEOUT, next_output(&mut socket, v2)).await {
+        Ok(Ok(Some(_))) => {}
+        Ok(Ok(None)) => output.fail("Error", "Console closed by remote.", EXIT_PROTOCOL),
+        Ok(Err((error, exit_code))) => output.fail("Error", error, exit_code),
+        Err(_) => {
+            let error = format!(
+                "Timed out after {}s waiting for the console of {}!",
+                LOGS_TIMEOUT.as_secs(),
+                args[0]
+            );
+            output.fail("Error", error, EXIT_TIMEOUT)
+        }
+    }
+    socket
+        .send(input_message(input, v2))
+        .await
+        .unwrap_or_else(|e| output.fail("Error", format!("Write error: {}", e), EXIT_PROTOCOL));
+
+    let mut captured = Vec::new();
+    if let Some(capture) = capture {
+        let deadline = deadline(capture);
+        let mut matched = false;
+        while !matched {
+            let data = match timeout_at(deadline, next_output(&mut socket, v2)).await {
+                Ok(Ok(Some(data))) => data,
+                Ok(Ok(None)) => output.fail("Error", "Console closed by remote.", EXIT_PROTOCOL),
+                Ok(Err((error, exit_code))) => output.fail("Error", error, exit_code),
+                Err(_) if until.is_some() => {
+                    let error = format!(
+                        "Timed out after {}s waiting for output matching {}!",
+                        capture.as_secs_f64(),
+                        opts["until"]
+                    );
+                    output.fail("Error", error, EXIT_TIMEOUT)
+                }
+                Err(_) => break,
+            };
+            for line in data.trim_end().lines() {
+                if !output.is_json() {
+                    println!("{}", line);
+                }
+                captured.push(line.to_owned());
+                matched = matched || until.as_ref().is_some_and(|until| until.is_match(line));
+            }
+        }
+    }
+
+    close_console(&mut socket)
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+    if output.is_json() && capture.is_some() {
+        print_json(&json!({ "success": true, "output": captured.join("\n") }));
+    } else if output.is_json() {
+        print_json(&json!({ "success": true }));
+    }
+}
+
+pub const COMMAND: Command = Command::new("exec", "Send a single command to an app's console")
+    .description(
+        "
+The command is sent as input to the app's console, like it would be typed into
+`octynectl console`, e.g. `octynectl exec survival -- say Hello!` (use -- so
+that the command isn't parsed as options).
+
+By default, octynectl exits right after the command is sent. Use --capture to
+print the app's output for some time afterwards, or --until to print it until
+a line matches a regex, e.g. `--until='Saved the game'`. If no line matches
+within the timeout (--timeout, which only applies to --until), octynectl exits
+with exit code 9.",
+    )
+    .args("[APP NAME] [COMMAND...]", 2, None)
+    .completes(ArgKind::Apps)
+    .options(&[
+        Opt::value(
+            "capture",
+            "duration",
+            "Print the app's output for this long after
+sending the command (e.g. 2s)",
+        )
+        .duration(),
+        Opt::value(
+            "until",
+            "regex",
+            "Print the app's output until a line matches
+this regex",
+        ),
+        TIMEOUT_OPTION,
+    ]);
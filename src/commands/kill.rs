@@ -1,47 +1,38 @@
-use std::{collections::HashMap, process::exit};
+use std::collections::HashMap;
 
-use crate::api::server::{post_server, PostServerAction};
+use super::wait::{TIMEOUT_OPTION, WAIT_OPTION};
+use crate::utils::actions::run_action;
+use crate::utils::cli::{ArgKind, Command};
+use crate::utils::config::Node;
+use crate::utils::misc::PARALLEL_OPTION;
+use crate::utils::selector::{
+    select_apps, ALL_OPTION, DRY_RUN_OPTION, EXCEPT_OPTION, SELECTOR_HELP, STATUS_OPTION,
+};
+use octynectl::api::server::{PostServerAction, STATUS_CRASHED, STATUS_OFFLINE};
 
-pub async fn kill_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        kill_cmd_help();
-        return;
-    } else if args.len() < 2 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "kill")
-        );
-        exit(1);
-    }
-
-    let mut any_errored = false;
-    for server_name in args[1..].iter() {
-        match post_server(server_name.to_string(), PostServerAction::Stop).await {
-            Ok(_) => {}
-            Err(e) => {
-                println!("Error killing {}: {}", server_name, e);
-                any_errored = true;
-            }
-        }
-    }
-    if any_errored {
-        exit(1);
-    }
+pub async fn kill_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let args = select_apps(args, &opts, &node, "kill").await;
+    run_action(
+        &args,
+        &opts,
+        &node,
+        PostServerAction::Stop,
+        "killing",
+        &[STATUS_OFFLINE, STATUS_CRASHED],
+    )
+    .await;
 }
 
-pub fn kill_cmd_help() {
-    println!(
-        "Kill an app managed by Octyne.
-
-Usage: octynectl kill [OPTIONS] [APP NAMES...]
-
-Options:
-    -h, --help               Print help information"
-    );
-}
+pub const COMMAND: Command = Command::new("kill", "Kill an app managed by Octyne")
+    .args("[APP NAMES...]", 0, None)
+    .description(SELECTOR_HELP)
+    .completes(ArgKind::Apps)
+    .options(&[
+        ALL_OPTION,
+        STATUS_OPTION,
+        EXCEPT_OPTION,
+        DRY_RUN_OPTION,
+        PARALLEL_OPTION,
+        WAIT_OPTION,
+        TIMEOUT_OPTION,
+    ]);
@@ -1,55 +1,43 @@
-use std::{collections::HashMap, process::exit};
+use std::collections::HashMap;
 
-use serde_json::Value;
+use crate::utils::cli::{Command, Opt};
+use crate::utils::config::{Node, FORMATS};
+use crate::utils::output::{print_json, Output};
+use octynectl::api::servers::ServerExtraInfo;
+use serde_json::json;
 
-use crate::api::servers::ServerExtraInfo;
-
-pub async fn list_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        list_cmd_help();
-        return;
-    } else if args.len() != 1 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "list")
-        );
-        exit(1);
-    }
-
-    // Check --format=json/csv/table flag
+pub async fn list_cmd(_args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    // Check --format=json/csv/table flag, --output=json implies --format=json
+    let output = Output::from_opts(&opts);
     let mut format = "table";
-    if let Some(format_value) = opts.get("format") {
-        if format_value != "json" && format_value != "csv" && format_value != "table" {
-            println!(
-                "Error: Invalid value for flag --format \"{}\"! (Valid values: json,csv,table)",
-                format_value
+    if output.is_json() {
+        format = "json";
+    } else if let Some(format_value) = opts.get("format").or(node.format.as_ref()) {
+        if !FORMATS.contains(&format_value.as_str()) {
+            let error = format!(
+                "Invalid value for flag --format \"{}\"! (Valid values: {})",
+                format_value,
+                FORMATS.join(",")
             );
-            exit(1);
+            output.fail("Error", error, 1);
         }
         format = format_value;
     }
 
-    let servers = crate::api::servers::get_servers(true)
+    let servers = octynectl::api::servers::get_servers(&node.client, true)
         .await
-        .unwrap_or_else(|e| {
-            println!("Error: {}", e);
-            exit(1);
-        });
+        .unwrap_or_else(|e| output.fail_api("Error", e));
 
-    if format == "json" {
+    if output.is_json() {
+        return print_json(&json!({ "success": true, "apps": servers }));
+    } else if format == "json" {
         println!("{}", serde_json::to_string_pretty(&servers).unwrap());
         return;
     } else if format == "csv" {
         println!("name,status,toDelete");
         for server in servers {
             let (name, status_value) = server;
-            let server_info = parse_server_info(status_value);
+            let server_info = ServerExtraInfo::from_value(status_value);
             let status = status_to_text(server_info.status).to_lowercase();
             println!("{},{},{}", name, status, server_info.to_delete);
         }
@@ -57,15 +45,15 @@ pub async fn list_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>
     }
 
     if servers.is_empty() {
-        println!("No apps are running under the local Octyne instance.");
+        println!("No apps are running under {}.", node.description());
         return;
     }
 
-    println!("Apps running under the local Octyne instance:\n");
+    println!("Apps running under {}:\n", node.description());
     let longest_name = servers.keys().map(|s| s.len()).max().unwrap_or(0);
     for server in servers {
         let (name, server_info_value) = server;
-        let server_info = parse_server_info(server_info_value);
+        let server_info = ServerExtraInfo::from_value(server_info_value);
         let mut info = status_to_text(server_info.status);
         if server_info.to_delete {
             info += " (marked for deletion)";
@@ -75,19 +63,6 @@ pub async fn list_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>
     }
 }
 
-fn parse_server_info(server_info_value: Value) -> ServerExtraInfo {
-    match server_info_value.as_i64() {
-        Some(status) => ServerExtraInfo {
-            status,
-            to_delete: false,
-        },
-        None => serde_json::from_value(server_info_value).unwrap_or(ServerExtraInfo {
-            status: -1,
-            to_delete: false,
-        }),
-    }
-}
-
 fn status_to_text(status: i64) -> String {
     match status {
         0 => "Offline",
@@ -106,17 +81,10 @@ fn pad_name(name: &str, longest_name: usize) -> String {
     padding
 }
 
-pub fn list_cmd_help() {
-    println!(
-        "List all apps under Octyne.
-
-Usage: octynectl list-apps [OPTIONS]
-
-Aliases: list, apps
-
-Options:
-    -h, --help           Print help information
-    --format=<format>    Format to print the list of apps in. Valid values: json,csv,table
-                         Default: table"
-    );
-}
+pub const COMMAND: Command = Command::new("list-apps", "List all apps under Octyne")
+    .aliases(&["list", "apps"])
+    .options(&[
+        Opt::value("format", "format", "Format to print the list of apps in")
+            .choices(&FORMATS)
+            .default("table"),
+    ]);
@@ -0,0 +1,57 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use crate::utils::cli::Command;
+use crate::utils::config::Node;
+use crate::utils::output::Output;
+use octynectl::api::{auth::get_login, client::OctyneClient, transport::Transport};
+
+pub async fn login_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let transport = if args.len() == 1 {
+        Transport::remote(&args[0], None).unwrap_or_else(|e| output.fail("Error", e, 1))
+    } else if node.client.transport().is_remote() {
+        node.client.transport().clone()
+    } else {
+        let error = "No Octyne URL specified! Pass a URL or use the --url option.";
+        output.fail("Error", error, 1);
+    };
+
+    // Keep stdout free of prompts when printing JSON.
+    if output.is_json() {
+        eprint!("Username: ");
+        std::io::stderr().flush().unwrap_or_default();
+    } else {
+        print!("Username: ");
+        std::io::stdout().flush().unwrap_or_default();
+    }
+    let mut username = String::new();
+    if let Err(err) = std::io::stdin().lock().read_line(&mut username) {
+        output.fail("Error", format!("Failed to read username! {}", err), 1);
+    }
+    let pass = match rpassword::prompt_password("Password: ") {
+        Ok(pass) => pass,
+        Err(err) => output.fail("Error", format!("Failed to read password! {}", err), 1),
+    };
+
+    let client = OctyneClient::new(transport.clone());
+    let token = get_login(&client, username.trim().to_owned(), pass)
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+    crate::utils::tokens::save_token(&transport.to_string(), &token)
+        .unwrap_or_else(|e| output.fail("Error", e, 1));
+    output.success(format!("Successfully logged in to {}", transport));
+}
+
+pub const COMMAND: Command = Command::new("login", "Log in to a remote Octyne instance")
+    .description(
+        "
+The token received from Octyne is saved in octynectl's config directory, and is
+used for all subsequent requests to the same URL. Alternatively, a token can be
+passed using the $OCTYNE_TOKEN environment variable.
+
+If no URL is specified, the URL from the --url option or $OCTYNE_URL is used.",
+    )
+    .args("(URL)", 0, Some(1));
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use crate::utils::cli::Command;
+use crate::utils::config::Node;
+use crate::utils::output::Output;
+use octynectl::api::{auth::get_logout, client::OctyneClient, transport::Transport};
+
+pub async fn logout_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let transport = if args.len() == 1 {
+        let token = crate::utils::tokens::get_token(&args[0]);
+        Transport::remote(&args[0], token).unwrap_or_else(|e| output.fail("Error", e, 1))
+    } else if node.client.transport().is_remote() {
+        node.client.transport().clone()
+    } else {
+        let error = "No Octyne URL specified! Pass a URL or use the --url option.";
+        output.fail("Error", error, 1);
+    };
+
+    // Even if Octyne fails to invalidate the token, forget it locally.
+    let result = get_logout(&OctyneClient::new(transport.clone())).await;
+    crate::utils::tokens::remove_token(&transport.to_string())
+        .unwrap_or_else(|e| output.fail("Error", e, 1));
+    match result {
+        Ok(_) => output.success(format!("Successfully logged out of {}", transport)),
+        Err(e) => output.fail_api("Error", e),
+    }
+}
+
+pub const COMMAND: Command = Command::new(
+    "logout",
+    "Log out of a remote Octyne instance, and forget the saved token",
+)
+.description("\nIf no URL is specified, the URL from the --url option or $OCTYNE_URL is used.")
+.args("(URL)", 0, Some(1));
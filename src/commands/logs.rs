@@ -1,10 +1,23 @@
 use std::fmt::Write;
-use std::{collections::HashMap, env, process::exit};
+use std::{collections::HashMap, env, process::exit, time::Duration};
 
-use crate::api::server::{connect_to_server_console_v1_fallback, ConsoleMessage};
+use super::console::{close_console, next_output, ping_message};
+use crate::help::invalid_usage;
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::log_filter::LogFilter;
+use crate::utils::output::{print_json, Output};
 use crossterm::tty::IsTty;
-use futures_util::StreamExt;
+use futures_util::{SinkExt, StreamExt};
 use minus::MinusError;
+use octynectl::api::common::{EXIT_FAILED, EXIT_PROTOCOL};
+use octynectl::api::server::{connect_to_server_console_v1_fallback, ConsoleMessage};
+use octynectl::api::transport::OctyneWebSocket;
+use serde_json::json;
+use tokio::{
+    select, signal,
+    time::{interval_at, Instant},
+};
 use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
 
 fn minus_page_lines(lines: &str) -> Result<(), MinusError> {
@@ -15,36 +28,27 @@ fn minus_page_lines(lines: &str) -> Result<(), MinusError> {
     Ok(())
 }
 
-pub async fn logs_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        logs_cmd_help();
-        return;
-    } else if args.len() != 2 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "logs")
-        );
-        exit(1);
-    }
+pub async fn logs_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
     let pager_env = env::var("PAGER");
-    let use_minus = opts.contains_key("use-builtin-pager") || pager_env.eq(&Ok(String::new()));
+    let pager_pref = node.pager.as_deref().unwrap_or("auto");
+    let use_minus = opts.contains_key("use-builtin-pager")
+        || pager_env.eq(&Ok(String::new()))
+        || pager_pref == "builtin";
     let no_pager = opts.contains_key("no-pager") // --no-pager is set
         || env::var("NOPAGER").eq(&Ok("true".to_string())) // $NOPAGER is set
+        || pager_pref == "none" // node profile has pager disabled
         || (!std::io::stdout().is_tty() && pager_env.is_err() && !use_minus); // no TTY or pager
+    if !opts.contains_key("grep") && (opts.contains_key("invert") || opts.contains_key("context")) {
+        let error = "--invert and --context can only be used with --grep";
+        output.fail_usage(invalid_usage(error, "logs"));
+    }
+    let mut filter = LogFilter::from_opts(&opts).unwrap_or_else(|e| output.fail("Error", e, 1));
 
     // Connect to WebSocket over Unix socket
-    let (socket, v2) = connect_to_server_console_v1_fallback(args[1].clone())
+    let (socket, v2) = connect_to_server_console_v1_fallback(&node.client, args[0].clone())
         .await
-        .unwrap_or_else(|e| {
-            println!("Error: {}", e);
-            exit(1);
-        });
+        .unwrap_or_else(|e| output.fail_api("Error", e));
 
     // Split the socket and then read a single message from it
     let (write, mut read) = socket.split();
@@ -52,14 +56,10 @@ pub async fn logs_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>
     loop {
         // Receive message from Octyne
         let item = match read.next().await {
-            Some(message) => message.unwrap_or_else(|e| {
-                println!("Error: {}", e);
-                exit(1);
-            }),
+            Some(message) => message.unwrap_or_else(|e| output.fail("Error", e, EXIT_PROTOCOL)),
             None => {
                 if !v2 {
-                    println!("Error: Received no message from Octyne!");
-                    exit(1);
+                    output.fail("Error", "Received no message from Octyne!", EXIT_PROTOCOL);
                 }
                 continue;
             }
@@ -67,31 +67,32 @@ pub async fn logs_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>
 
         // Handle the message
         if item.is_close() {
-            println!("Error: Received close message from Octyne!");
-            exit(1);
+            output.fail(
+                "Error",
+                "Received close message from Octyne!",
+                EXIT_PROTOCOL,
+            );
         } else if v2 && !item.is_text() {
             continue;
         }
 
-        let item = item.to_text().unwrap_or_else(|e| {
-            println!("Error: {}", e);
-            exit(1);
-        });
+        let item = item
+            .to_text()
+            .unwrap_or_else(|e| output.fail("Error", e, EXIT_PROTOCOL));
         if v2 {
             // Parse message
             let json: ConsoleMessage = match serde_json::from_str(item) {
                 Ok(json) => json,
                 Err(e) => {
-                    println!("Error: Received corrupt message from Octyne! {}", e);
-                    exit(1);
+                    let error = format!("Received corrupt message from Octyne! {}", e);
+                    output.fail("Error", error, EXIT_PROTOCOL);
                 }
             };
             if json.r#type == "output" {
                 logs = json.data;
                 break;
             } else if json.r#type == "error" {
-                println!("Error: {}", json.message);
-                exit(1);
+                output.fail("Error", json.message, EXIT_FAILED);
             } // Discard the rest
         } else {
             logs = item.to_owned();
@@ -99,24 +100,27 @@ pub async fn logs_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>
         }
     }
 
+    let mut socket = read
+        .reunite(write)
+        .unwrap_or_else(|e| output.fail("Error", e, 1));
+    let logs = filter.filter_logs(&logs);
+    if opts.contains_key("follow") {
+        return follow_logs(socket, v2, logs, filter, output).await;
+    }
+
     // Close the WebSocket connection.
-    let mut socket = read.reunite(write).unwrap_or_else(|e| {
-        println!("Error: {}", e);
-        exit(1);
-    });
     socket
         .close(Some(CloseFrame {
             code: CloseCode::Normal,
             reason: "Done".into(),
         }))
         .await
-        .unwrap_or_else(|e| {
-            println!("Error: {}", e);
-            exit(1);
-        });
+        .unwrap_or_else(|e| output.fail("Error", e, 1));
 
     // Log the output.
-    if no_pager {
+    if output.is_json() {
+        return print_json(&json!({ "success": true, "name": args[0], "logs": logs }));
+    } else if no_pager {
         return println!("{}", logs);
     }
     #[cfg(target_family = "unix")]
@@ -131,10 +135,58 @@ pub async fn logs_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>
     });
 }
 
-pub fn logs_cmd_help() {
-    println!(
-        "Get the output logs of an app.
+// Prints the logs so far, then the app's output as it arrives, until Ctrl-C is pressed.
+// Output is filtered line by line, with the same options as the logs so far (except --tail). Lines
+// split across several outputs are only filtered once complete.
+async fn follow_logs(
+    mut socket: OctyneWebSocket,
+    v2: bool,
+    logs: String,
+    mut filter: LogFilter,
+    output: Output,
+) {
+    let print = |data: &str| match output.is_json() {
+        true => println!("{}", json!({ "type": "output", "data": data })),
+        false => println!("{}", data.trim_end_matches(['\r', '\n'])),
+    };
+    let print_lines = |lines: Vec<String>| {
+        if !lines.is_empty() {
+            print(&lines.join("\n"));
+        }
+    };
+    if filter.is_empty() || !logs.is_empty() {
+        print(&logs);
+    }
+    let ping_duration = Duration::from_secs(5);
+    let mut interval = interval_at(Instant::now() + ping_duration, ping_duration);
+    loop {
+        select! {
+            data = next_output(&mut socket, v2) => match data {
+                Ok(Some(data)) if filter.is_empty() => print(&data),
+                Ok(Some(data)) => print_lines(filter.filter_chunk(&data)),
+                Ok(None) => {
+                    print_lines(filter.flush());
+                    exit(0)
+                }
+                Err((error, exit_code)) => output.fail("Error", error, exit_code),
+            },
+            _ = interval.tick(), if v2 => {
+                if let Err(e) = socket.send(ping_message()).await {
+                    output.fail("Error", format!("Write error: {}", e), EXIT_PROTOCOL);
+                }
+            }
+            _ = signal::ctrl_c() => break,
+        }
+    }
+    print_lines(filter.flush());
+    close_console(&mut socket)
+        .await
+        .unwrap_or_else(|e| output.fail_api("Error", e));
+}
 
+pub const COMMAND: Command = Command::new("logs", "Get the output logs of an app")
+    .description(
+        "
 If $PAGER is set, it will be used to display the logs, else, less will be used.
 On Windows, a built-in pager library will be used, even if $PAGER is set. You
 can use it on Unix-like systems too (e.g. Linux, macOS) by passing the
@@ -144,11 +196,50 @@ The pager can be disabled entirely by setting the $NOPAGER environment variable
 to `true`, or by using the `--no-pager` flag. If stdout is not a terminal, the
 pager will be disabled unless $PAGER or the `--use-builtin-pager` flag is set.
 
-Usage: octynectl logs [OPTIONS] [APP NAME]
+With --follow, the app's output keeps being printed as it arrives, like
+`tail -f`. With --output=json, each output is printed as a JSON object on its
+own line, like `console`.
 
-Options:
-    -h, --help               Print help information
-    --no-pager               Don't use a pager to display logs
-    --use-builtin-pager      Use the built-in pager to display logs"
-    );
-}
+The logs can be filtered before being shown: --tail shows only the last lines,
+--since shows only lines logged after a time, and --grep shows only lines
+matching a regex (or not matching it, with --invert), like `grep`. --context
+shows lines around each match, with groups of lines separated by `--`. With
+--follow, these also apply to the output as it arrives, except --tail.
+
+--since accepts a duration (e.g. `--since=30m`) or a local date and/or time
+(e.g. `--since=10:30`, `--since='2024-01-15 10:30'`), which is compared with
+the timestamp at the start of log lines, e.g. `[10:30:00]` (Minecraft),
+`2024-01-15 10:30:00,000` (log4j) or `2024-01-15T10:30:00Z` (ISO 8601).
+Lines before the first timestamp at or after this time are left out.",
+    )
+    .args("[APP NAME]", 1, Some(1))
+    .completes(ArgKind::Apps)
+    .options(&[
+        Opt::flag(
+            "follow",
+            "Keep printing the app's output as it arrives,
+until Ctrl-C is pressed (the pager is not used)",
+        )
+        .short('f'),
+        Opt::value("tail", "lines", "Only show the last N lines of the logs").number(),
+        Opt::value(
+            "since",
+            "time",
+            "Only show logs since a duration ago (e.g. 10m)
+or a time (e.g. 10:30, 2024-01-15 10:30)",
+        ),
+        Opt::value("grep", "regex", "Only show lines matching this regex"),
+        Opt::flag("invert", "Only show lines not matching --grep"),
+        Opt::value(
+            "context",
+            "lines",
+            "Show this many lines before and after each
+line matching --grep",
+        )
+        .number(),
+        Opt::flag("no-pager", "Don't use a pager to display logs"),
+        Opt::flag(
+            "use-builtin-pager",
+            "Use the built-in pager to display logs",
+        ),
+    ]);
@@ -0,0 +1,56 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::help::{invalid_usage_str, unknown_subcommand_str, ROOT};
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::man::{man_page, page_name, page_paths};
+use crate::utils::output::Output;
+
+pub fn man_cmd(args: Vec<String>, opts: HashMap<String, String>) {
+    let mut path = vec![&ROOT];
+    for (index, name) in args.iter().enumerate() {
+        match path[path.len() - 1].find_subcommand(name) {
+            Some(subcommand) if !subcommand.hidden => path.push(subcommand),
+            _ => {
+                let error = unknown_subcommand_str(args[..=index].join(" "));
+                Output::from_opts(&opts).fail_usage(invalid_usage_str(error, "man".to_string()))
+            }
+        }
+    }
+
+    let out_dir = match opts.get("out-dir") {
+        Some(out_dir) => Path::new(out_dir),
+        None => {
+            print!("{}", man_page(&path));
+            return;
+        }
+    };
+    let output = Output::from_opts(&opts);
+    if let Err(e) = fs::create_dir_all(out_dir) {
+        let error = format!("Failed to create {}! {}", out_dir.display(), e);
+        output.fail("Error", error, 1);
+    }
+    for path in page_paths(path) {
+        let file = out_dir.join(format!("{}.1", page_name(&path)));
+        if let Err(e) = fs::write(&file, man_page(&path)) {
+            let error = format!("Failed to write {}! {}", file.display(), e);
+            output.fail("Error", error, 1);
+        }
+        println!("Wrote {}", file.display());
+    }
+}
+
+pub const COMMAND: Command = Command::new("man", "Generate man pages for octynectl")
+    .description(
+        "
+Without --out-dir, the man page for octynectl (or the specified subcommand) is
+printed to stdout e.g. `octynectl man config edit | man -l -`. With --out-dir,
+man pages for octynectl (or the specified subcommand) and all its subcommands
+are written to that directory e.g. octynectl.1, octynectl-config-edit.1.",
+    )
+    .args("(SUBCOMMAND...)", 0, None)
+    .completes(ArgKind::Subcommands)
+    .options(&[Opt::value(
+        "out-dir",
+        "path",
+        "Directory to write all the man pages to",
+    )]);
@@ -1,11 +1,20 @@
 pub mod accounts;
+pub mod completions;
 pub mod config;
 pub mod console;
+pub mod exec;
 pub mod kill;
 pub mod list;
+pub mod login;
+pub mod logout;
 pub mod logs;
+pub mod man;
+pub mod nodes;
 pub mod restart;
+pub mod rolling_restart;
 pub mod start;
 pub mod status;
 pub mod stop;
+pub mod top;
 pub mod version;
+pub mod wait;
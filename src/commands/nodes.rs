@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::help::invalid_usage;
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::{read_config, write_config, NodeConfig, FORMATS, PAGERS};
+use crate::utils::output::{print_json, Output, Results};
+use serde_json::Value;
+
+pub fn nodes_list_cmd(_args: Vec<String>, opts: HashMap<String, String>) {
+    let output = Output::from_opts(&opts);
+    let config = read_config().unwrap_or_else(|e| output.fail("Error", e, 1));
+    if output.is_json() {
+        let mut value = serde_json::to_value(&config).unwrap();
+        value["success"] = Value::Bool(true);
+        return print_json(&value);
+    } else if config.nodes.is_empty() {
+        println!("No nodes are configured! Add one using `octynectl nodes add`.");
+        return;
+    }
+
+    println!("Nodes configured in octynectl (* = default):\n");
+    let longest_name = config.nodes.keys().map(|s| s.len()).max().unwrap_or(0);
+    for (name, node) in config.nodes.iter() {
+        let marker = match config.default_node.as_ref() == Some(name) {
+            true => "*",
+            false => " ",
+        };
+        println!(
+            "  {} {:width$} | {}",
+            marker,
+            name,
+            node.location(),
+            width = longest_name
+        );
+    }
+}
+
+pub fn nodes_add_cmd(args: Vec<String>, opts: HashMap<String, String>) {
+    let output = Output::from_opts(&opts);
+    if opts.contains_key("socket") && opts.contains_key("port") {
+        let error = "--socket and --port cannot be used together";
+        output.fail_usage(invalid_usage(error, "nodes add"));
+    }
+    let mut node = NodeConfig {
+        socket: opts.get("socket").cloned(),
+        url: opts.get("url").cloned(),
+        token_env: opts.get("token-env").cloned(),
+        format: opts.get("format").cloned(),
+        pager: opts.get("pager").cloned(),
+    };
+    if let Some(port) = opts.get("port") {
+        match port.parse::<u16>() {
+            Ok(port) if port != 0 => {
+                let path = crate::utils::misc::octyne_path_for_port(port);
+                node.socket = Some(path.to_string_lossy().into_owned());
+            }
+            _ => output.fail(
+                "Error",
+                format!("Invalid value for flag --port \"{}\"!", port),
+                1,
+            ),
+        }
+    }
+    if node.url.is_some() == node.socket.is_some() {
+        let error = "Exactly one of --url, --socket or --port must be specified!";
+        output.fail("Error", error, 1);
+    } else if let Some(url) = &node.url {
+        if let Err(e) = octynectl::api::transport::Transport::remote(url, None) {
+            output.fail("Error", e, 1);
+        }
+    }
+    let mut config = read_config().unwrap_or_else(|e| output.fail("Error", e, 1));
+    let replaced = config.nodes.insert(args[0].to_owned(), node).is_some();
+    if opts.contains_key("default") {
+        config.default_node = Some(args[0].to_owned());
+    }
+    write_config(&config).unwrap_or_else(|e| output.fail("Error", e, 1));
+    match replaced {
+        true => output.success(format!("Successfully updated node {}", args[0])),
+        false => output.success(format!("Successfully added node {}", args[0])),
+    }
+}
+
+pub fn nodes_remove_cmd(args: Vec<String>, opts: HashMap<String, String>) {
+    let output = Output::from_opts(&opts);
+    let mut config = read_config().unwrap_or_else(|e| output.fail("Error", e, 1));
+    let mut results = Results::new(output);
+    for name in args.iter() {
+        if config.nodes.remove(name).is_none() {
+            let context = format!("Error removing node {}", name);
+            let error = format!("No node named {} exists!", name);
+            results.failure(name, &context, error, 1);
+        } else {
+            if config.default_node.as_ref() == Some(name) {
+                config.default_node = None;
+            }
+            results.success(name);
+        }
+    }
+    write_config(&config).unwrap_or_else(|e| output.fail("Error", e, 1));
+    results.finish(Some("Successfully removed specified nodes!"));
+}
+
+pub fn nodes_set_default_cmd(args: Vec<String>, opts: HashMap<String, String>) {
+    let output = Output::from_opts(&opts);
+    let mut config = read_config().unwrap_or_else(|e| output.fail("Error", e, 1));
+    if !config.nodes.contains_key(&args[0]) {
+        output.fail("Error", format!("No node named {} exists!", args[0]), 1);
+    }
+    config.default_node = Some(args[0].to_owned());
+    write_config(&config).unwrap_or_else(|e| output.fail("Error", e, 1));
+    output.success(format!("Successfully set default node to {}", args[0]));
+}
+
+pub const COMMAND: Command = Command::new("nodes", "Manage nodes octynectl can connect to")
+    .description(
+        "
+Nodes are saved in octynectl's config file (e.g. ~/.config/octynectl/config.json
+on Linux), and can be selected using the --node=NAME option, or by setting the
+$OCTYNE_NODE environment variable. If neither are set, the default node is used.",
+    )
+    .aliases(&["node"])
+    .subcommands(&[
+        LIST_COMMAND,
+        ADD_COMMAND,
+        REMOVE_COMMAND,
+        SET_DEFAULT_COMMAND,
+    ]);
+
+pub const LIST_COMMAND: Command = Command::new("list", "List all nodes").aliases(&["show"]);
+
+pub const ADD_COMMAND: Command = Command::new("add", "Add a new node (or update an existing one)")
+    .description(
+        "
+Exactly one of --url, --socket or --port must be specified. For remote nodes,
+the token saved by `octynectl --node=NAME login` is used, unless --token-env is
+specified, in which case the token is read from that environment variable.",
+    )
+    .aliases(&["create"])
+    .args("[NAME]", 1, Some(1))
+    .options(&[
+        Opt::value("url", "url", "URL of a remote Octyne instance"),
+        Opt::value(
+            "socket",
+            "path",
+            "Path to the Unix socket of a local Octyne instance",
+        ),
+        Opt::value(
+            "port",
+            "port",
+            "Port of a local Octyne instance, used to find its Unix socket",
+        )
+        .number(),
+        Opt::value(
+            "token-env",
+            "var",
+            "Environment variable to read the token from",
+        ),
+        Opt::value(
+            "format",
+            "format",
+            "Default format for commands with a --format option",
+        )
+        .choices(&FORMATS),
+        Opt::value("pager", "pager", "Pager preference for logs").choices(&PAGERS),
+        Opt::flag("default", "Set this node as the default node"),
+    ]);
+
+pub const REMOVE_COMMAND: Command = Command::new("remove", "Remove nodes")
+    .aliases(&["delete"])
+    .args("[NAMES...]", 1, None)
+    .completes(ArgKind::Nodes);
+
+pub const SET_DEFAULT_COMMAND: Command =
+    Command::new("set-default", "Set the node to use by default")
+        .args("[NAME]", 1, Some(1))
+        .completes(ArgKind::Nodes);
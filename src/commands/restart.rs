@@ -1,59 +1,132 @@
-use std::{collections::HashMap, process::exit};
+use std::{collections::HashMap, time::Duration};
 
-use crate::api::server::{post_server, PostServerAction};
+use super::wait::{timeout, wait_for_app, POLL_INTERVAL, TIMEOUT_OPTION, WAIT_OPTION};
+use crate::utils::actions::act_on_apps;
+use crate::utils::announce::{announce, countdown, ANNOUNCE_OPTION, COUNTDOWN_OPTION};
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::misc::{parse_duration, PARALLEL_OPTION};
+use crate::utils::output::Output;
+use crate::utils::selector::{
+    select_apps, selector_help, ALL_OPTION, DRY_RUN_OPTION, EXCEPT_OPTION, STATUS_OPTION,
+};
+use octynectl::api::common::ApiError;
+use octynectl::api::server::{
+    post_server, wait_for_status, PostServerAction, STATUS_CRASHED, STATUS_OFFLINE, STATUS_ONLINE,
+};
 
-pub async fn restart_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        restart_cmd_help();
-        return;
-    } else if args.len() < 2 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "start")
-        );
-        exit(1);
-    }
+pub const KILL_OPTION: Opt = Opt::flag(
+    "kill",
+    "Kill the app instead of gracefully stopping it before restarting",
+)
+.short('k');
+
+pub const GRACE_PERIOD_OPTION: Opt = Opt::value(
+    "grace-period",
+    "duration",
+    "Time to wait for the app to stop gracefully
+before killing it",
+)
+.duration()
+.default("30s");
 
-    let action = match opts.contains_key("k") || opts.contains_key("kill") {
+pub async fn restart_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let marks = countdown(&opts, "restart");
+    let args = select_apps(args, &opts, &node, "restart").await;
+    announce(&args, &marks, &opts, &node).await;
+    let progress = !Output::from_opts(&opts).is_json();
+    act_on_apps(&args, &opts, |name| {
+        restart_app(name, &opts, &node, progress)
+    })
+    .await;
+}
+
+// Stops the app, waits for it to stop (killing it if it doesn't stop within the grace period),
+// then starts it (and waits for it with --wait). With progress, killing the app is announced.
+// Errors are returned along with their context.
+pub async fn restart_app(
+    name: &str,
+    opts: &HashMap<String, String>,
+    node: &Node,
+    progress: bool,
+) -> Result<(), (String, ApiError)> {
+    let kill = opts.contains_key("kill");
+    let action = match kill {
         true => PostServerAction::Stop,
         false => PostServerAction::Term,
     };
+    post_server(&node.client, name.to_owned(), action)
+        .await
+        .map_err(|e| (format!("Error stopping {} before restart", name), e))?;
 
-    let mut any_errored = false;
-    for server_name in args[1..].iter() {
-        match post_server(server_name.to_string(), action.clone()).await {
-            Ok(_) => match post_server(server_name.to_string(), PostServerAction::Start).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error starting {} after restart: {}", server_name, e);
-                    any_errored = true;
-                }
-            },
-            Err(e) => {
-                println!("Error stopping {} before restart: {}", server_name, e);
-                any_errored = true;
+    let stopped = [STATUS_OFFLINE, STATUS_CRASHED];
+    let grace_period = opts
+        .get("grace-period")
+        .and_then(|grace_period| parse_duration(grace_period).ok())
+        .unwrap_or(Duration::from_secs(30));
+    let timeout = timeout(opts);
+    let context = format!("Error waiting for {} to stop before restart", name);
+    match wait_for_status(
+        &node.client,
+        name.to_owned(),
+        &stopped,
+        grace_period,
+        POLL_INTERVAL,
+    )
+    .await
+    {
+        Ok(_) => {}
+        Err(ApiError::Timeout(_)) if !kill => {
+            if progress {
+                println!(
+                    "{} did not stop within {}s, killing it...",
+                    name,
+                    grace_period.as_secs_f64()
+                );
             }
+            post_server(&node.client, name.to_owned(), PostServerAction::Stop)
+                .await
+                .map_err(|e| (format!("Error killing {} before restart", name), e))?;
+            wait_for_status(
+                &node.client,
+                name.to_owned(),
+                &stopped,
+                timeout,
+                POLL_INTERVAL,
+            )
+            .await
+            .map_err(|e| (context, e))?;
         }
+        Err(e) => return Err((context, e)),
     }
-    if any_errored {
-        exit(1);
-    }
-}
-
-pub fn restart_cmd_help() {
-    println!(
-        "Restart an app managed by Octyne.
 
-Usage: octynectl restart [OPTIONS] [APP NAMES...]
-
-Options:
-    -h, --help        Print help information
-    -k, --kill        Kill the app instead of gracefully stopping it before restarting"
-    );
+    post_server(&node.client, name.to_owned(), PostServerAction::Start)
+        .await
+        .map_err(|e| (format!("Error starting {} after restart", name), e))?;
+    wait_for_app(name, &[STATUS_ONLINE], opts, node).await
 }
+
+pub const COMMAND: Command = Command::new("restart", "Restart an app managed by Octyne")
+    .args("[APP NAMES...]", 0, None)
+    .description(concat!(
+        "
+The app is gracefully stopped (or killed with --kill), and started once it has
+stopped. If it does not stop within the grace period, it is killed. With --wait,
+octynectl also waits for the app to be online again before exiting.",
+        "\n",
+        selector_help!()
+    ))
+    .completes(ArgKind::Apps)
+    .options(&[
+        KILL_OPTION,
+        GRACE_PERIOD_OPTION,
+        ALL_OPTION,
+        STATUS_OPTION,
+        EXCEPT_OPTION,
+        DRY_RUN_OPTION,
+        ANNOUNCE_OPTION,
+        COUNTDOWN_OPTION,
+        PARALLEL_OPTION,
+        WAIT_OPTION,
+        TIMEOUT_OPTION,
+    ]);
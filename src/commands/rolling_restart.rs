@@ -0,0 +1,141 @@
+use std::{collections::HashMap, time::Duration};
+
+use super::restart::{restart_app, GRACE_PERIOD_OPTION, KILL_OPTION};
+use super::wait::{timeout, POLL_INTERVAL, TIMEOUT_OPTION};
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::misc::parse_duration;
+use crate::utils::output::{Output, Results};
+use crate::utils::selector::{
+    select_apps, selector_help, ALL_OPTION, DRY_RUN_OPTION, EXCEPT_OPTION, STATUS_OPTION,
+};
+use octynectl::api::common::ApiError;
+use octynectl::api::server::{wait_for_status, STATUS_CRASHED, STATUS_ONLINE};
+
+// Time an app has to be online after being started, unless --wait-online is passed.
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn rolling_restart_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let args = select_apps(args, &opts, &node, "rolling-restart").await;
+    let output = Output::from_opts(&opts);
+    let mut results = Results::new(output);
+    let delay = opts
+        .get("delay")
+        .and_then(|delay| parse_duration(delay).ok())
+        .unwrap_or(Duration::ZERO);
+    for (index, server_name) in args.iter().enumerate() {
+        if !output.is_json() {
+            println!(
+                "Restarting {} ({}/{})...",
+                server_name,
+                index + 1,
+                args.len()
+            );
+        }
+        let last = index + 1 == args.len();
+        let delay = if last { Duration::ZERO } else { delay };
+        match roll_app(server_name, delay, &opts, &node, !output.is_json()).await {
+            Ok(_) => results.success(server_name),
+            Err((context, e)) => {
+                let exit_code = e.exit_code();
+                results.failure_api(server_name, &context, e);
+                // The remaining apps are reported too, so it's clear they were left untouched.
+                let error = format!("Not restarted, aborted after {} failed", server_name);
+                for name in args[index + 1..].iter() {
+                    results.failure(name, &format!("Skipped {}", name), &error, exit_code);
+                }
+                break;
+            }
+        }
+    }
+    results.finish(Some("Restarted all apps successfully."));
+}
+
+// Restarts the app, waits for it to be online (for up to --timeout with --wait-online), then keeps
+// an eye on it for the delay before the next app is restarted, failing if it crashes meanwhile.
+async fn roll_app(
+    name: &str,
+    delay: Duration,
+    opts: &HashMap<String, String>,
+    node: &Node,
+    progress: bool,
+) -> Result<(), (String, ApiError)> {
+    restart_app(name, opts, node, progress).await?;
+    let crashed = || {
+        (
+            format!("Error restarting {}", name),
+            ApiError::Failed(format!("{} crashed after restarting!", name)),
+        )
+    };
+
+    let timeout = match opts.contains_key("wait-online") {
+        true => timeout(opts),
+        false => START_TIMEOUT,
+    };
+    let response = wait_for_status(
+        &node.client,
+        name.to_owned(),
+        &[STATUS_ONLINE, STATUS_CRASHED],
+        timeout,
+        POLL_INTERVAL,
+    )
+    .await
+    .map_err(|e| (format!("Error waiting for {} to start", name), e))?;
+    if response.status == STATUS_CRASHED {
+        return Err(crashed());
+    }
+
+    // wait_for_status times out if the app doesn't crash during the delay, which is what we want.
+    match wait_for_status(
+        &node.client,
+        name.to_owned(),
+        &[STATUS_CRASHED],
+        delay,
+        POLL_INTERVAL,
+    )
+    .await
+    {
+        Ok(_) => Err(crashed()),
+        Err(ApiError::Timeout(_)) => Ok(()),
+        Err(e) => Err((format!("Error checking on {}", name), e)),
+    }
+}
+
+pub const COMMAND: Command = Command::new(
+    "rolling-restart",
+    "Restart apps one by one, stopping if one crashes",
+)
+.description(concat!(
+    "
+Each app is restarted like with `octynectl restart`, and the next app is only
+restarted once the previous one is online again and the delay has passed. An app
+must be online within 10 seconds of being started, or within --timeout with
+--wait-online. If an app fails to restart, isn't online in time, or crashes
+before the next app is restarted, the rolling restart is aborted and the
+remaining apps are left untouched (and reported as not restarted).",
+    "\n",
+    selector_help!()
+))
+.args("[APP NAMES...]", 0, None)
+.completes(ArgKind::Apps)
+.options(&[
+    Opt::value(
+        "delay",
+        "duration",
+        "Time to wait after restarting an app before
+restarting the next one (e.g. 30s, 5m)",
+    )
+    .duration(),
+    Opt::flag(
+        "wait-online",
+        "Wait up to --timeout (instead of 10s) for each
+app to be online before moving on to the next one",
+    ),
+    KILL_OPTION,
+    GRACE_PERIOD_OPTION,
+    ALL_OPTION,
+    STATUS_OPTION,
+    EXCEPT_OPTION,
+    DRY_RUN_OPTION,
+    TIMEOUT_OPTION,
+]);
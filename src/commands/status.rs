@@ -1,65 +1,386 @@
-use std::{collections::HashMap, process::exit};
-
-use crate::api::server::get_server;
-
-// TODO: Support multiple apps down the line
-pub async fn status_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        status_cmd_help();
-        return;
-    } else if args.len() != 2 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "status")
-        );
-        exit(1);
+use std::{collections::HashMap, io::stdout, process::exit, time::Duration};
+
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    style::Stylize,
+    terminal::{Clear, ClearType},
+    tty::IsTty,
+};
+use futures_util::future::join_all;
+use serde_json::{json, Value};
+use tokio::{select, signal};
+
+use crate::help::invalid_usage;
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::{Node, FORMATS};
+use crate::utils::output::{print_json, Output};
+use octynectl::api::common::{ApiError, EXIT_FAILED};
+use octynectl::api::server::{get_server, GetServerResponse, STATUS_ONLINE};
+use octynectl::api::servers::get_servers;
+
+pub const SORTS: [&str; 4] = ["name", "cpu", "memory", "uptime"];
+
+pub async fn status_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let all = opts.contains_key("all");
+    if all && !args.is_empty() {
+        output.fail_usage(invalid_usage(
+            "--all cannot be used with app names",
+            "status",
+        ));
+    } else if !all && args.is_empty() {
+        output.fail_usage(invalid_usage("No apps specified", "status"));
     }
 
-    let json = match get_server(args[1].clone()).await {
-        Ok(json) => json,
-        Err(e) => {
-            println!("Error: {}", e);
-            exit(1);
+    if let Some(watch) = opts.get("watch") {
+        if args.len() != 1 {
+            let error = invalid_usage("--watch can only be used with a single app", "status");
+            output.fail_usage(error);
+        }
+        let seconds = match watch.as_str() {
+            "" => 2,
+            watch => watch.parse::<u64>().unwrap_or_default(),
+        };
+        if seconds == 0 {
+            output.fail_usage(invalid_usage("--watch must be at least 1 second", "status"));
         }
+        let fail_if_down = opts.contains_key("fail-if-down");
+        return status_watch(&args[0], seconds, fail_if_down, output, &node).await;
+    } else if opts.contains_key("fail-if-down") {
+        output.fail_usage(invalid_usage("--fail-if-down requires --watch", "status"));
+    }
+
+    // A single app is shown in detail, unless a table is explicitly requested.
+    if args.len() == 1 && !opts.contains_key("format") && !opts.contains_key("sort") {
+        return status_single(&args[0], output, &node).await;
+    }
+
+    let format = match output.is_json() {
+        true => "json",
+        false => opts
+            .get("format")
+            .or(node.format.as_ref())
+            .map_or("table", |format| format.as_str()),
+    };
+    let names = match all {
+        true => get_servers(&node.client, false)
+            .await
+            .unwrap_or_else(|e| output.fail_api("Error", e))
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect(),
+        false => args,
     };
 
-    println!("\nStatus of app `{}`:", args[1]);
-    println!("================={}", "=".repeat(args[1].len()));
-    println!(
-        "Status: {}{}",
-        parse_status(json.status),
-        parse_to_delete(json.to_delete)
-    );
-    println!("CPU usage: {:.2}%", json.cpu_usage);
-    let memory_usage = json.memory_usage as f64 / 1024.0 / 1024.0;
-    let total_memory = json.total_memory as f64 / 1024.0 / 1024.0;
-    let memory_percentage = memory_usage / total_memory * 100.0;
-    println!(
-        "Memory usage: {:.2}% ({:.2} MB / {:.0} MB)",
-        memory_percentage, memory_usage, total_memory
+    // Fetch the status of every app concurrently, over the same pooled client.
+    let responses = join_all(
+        names
+            .iter()
+            .map(|name| get_server(&node.client, name.clone())),
+    )
+    .await;
+    let mut apps: Vec<(String, GetServerResponse)> = Vec::new();
+    let mut errors: Vec<(String, ApiError)> = Vec::new();
+    for (name, response) in names.into_iter().zip(responses) {
+        match response {
+            Ok(info) => apps.push((name, info)),
+            Err(e) => errors.push((name, e)),
+        }
+    }
+    sort_apps(
+        &mut apps,
+        opts.get("sort").map_or("name", |sort| sort.as_str()),
     );
-    println!("Uptime: {}", parse_duration(json.uptime));
+    let exit_code = errors.last().map_or(0, |(_, e)| e.exit_code());
+
+    match format {
+        "json" => {
+            let mut entries = apps
+                .iter()
+                .map(|(name, info)| status_json(name, info))
+                .collect::<Vec<Value>>();
+            entries.extend(errors.iter().map(|(name, e)| {
+                json!({
+                    "name": name,
+                    "success": false,
+                    "error": e.to_string(),
+                    "exitCode": e.exit_code(),
+                })
+            }));
+            print_json(&json!({ "success": exit_code == 0, "apps": entries }));
+        }
+        "csv" => {
+            println!("name,status,cpuUsage,memoryUsage,totalMemory,uptime,toDelete");
+            for (name, info) in apps.iter() {
+                println!(
+                    "{},{},{:.2},{},{},{},{}",
+                    name,
+                    parse_status(info.status).to_lowercase(),
+                    info.cpu_usage,
+                    info.memory_usage,
+                    info.total_memory,
+                    info.uptime / 1_000_000_000,
+                    info.to_delete
+                );
+            }
+            // Keep stdout valid CSV.
+            for (name, e) in errors.iter() {
+                eprintln!("Error getting status of {}: {}", name, e);
+            }
+        }
+        _ => {
+            if !apps.is_empty() {
+                print_table(&apps);
+            } else if errors.is_empty() {
+                println!("No apps are running under {}.", node.description());
+            }
+            for (name, e) in errors.iter() {
+                println!("Error getting status of {}: {}", name, e);
+            }
+        }
+    }
+    if exit_code != 0 {
+        exit(exit_code);
+    }
 }
 
-pub fn status_cmd_help() {
-    println!(
-        "Get the status of an app.
+async fn status_single(name: &str, output: Output, node: &Node) {
+    let json = match get_server(&node.client, name.to_owned()).await {
+        Ok(json) => json,
+        Err(e) => output.fail_api("Error", e),
+    };
+    if output.is_json() {
+        return print_json(&status_json(name, &json));
+    }
+
+    println!("\nStatus of app `{}`:", name);
+    println!("================={}", "=".repeat(name.len()));
+    for line in status_lines(&json, None, false) {
+        println!("{}", line);
+    }
+}
 
-Usage: octynectl status [OPTIONS] [APP NAME]
+// Polls the app until interrupted, redrawing in place if stdout is a terminal, else printing each
+// update one after the other (or one JSON object per line with --output=json).
+async fn status_watch(name: &str, seconds: u64, fail_if_down: bool, output: Output, node: &Node) {
+    let redraw = !output.is_json() && stdout().is_tty();
+    let mut previous: Option<GetServerResponse> = None;
+    let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+    loop {
+        select! {
+            _ = interval.tick() => {}
+            _ = signal::ctrl_c() => return,
+        }
+        let response = get_server(&node.client, name.to_owned()).await;
+        if output.is_json() {
+            match &response {
+                Ok(info) => println!("{}", status_json(name, info)),
+                Err(e) => println!(
+                    "{}",
+                    json!({
+                        "name": name,
+                        "success": false,
+                        "error": e.to_string(),
+                        "exitCode": e.exit_code(),
+                    })
+                ),
+            }
+        } else {
+            if redraw {
+                execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0)).unwrap_or_default();
+            }
+            let header = format!(
+                "Status of app `{}` (every {}s, press Ctrl-C to exit):",
+                name, seconds
+            );
+            println!("{}", header);
+            println!("{}", "=".repeat(header.chars().count()));
+            match &response {
+                Ok(info) => {
+                    for line in status_lines(info, previous.as_ref(), redraw) {
+                        println!("{}", line);
+                    }
+                }
+                Err(e) => println!("Error: {}", e),
+            }
+            if !redraw {
+                println!();
+            }
+        }
+
+        // The app is also considered down if its status can't be fetched, e.g. if it was deleted.
+        match response {
+            Ok(info) if fail_if_down && info.status != STATUS_ONLINE => {
+                if !output.is_json() {
+                    println!("App `{}` is no longer online!", name);
+                }
+                exit(EXIT_FAILED);
+            }
+            Ok(info) => previous = Some(info),
+            Err(e) if fail_if_down => exit(e.exit_code()),
+            Err(_) => {}
+        }
+    }
+}
 
-Aliases: info
+fn status_json(name: &str, info: &GetServerResponse) -> Value {
+    let mut value = serde_json::to_value(info).unwrap();
+    value["name"] = Value::String(name.to_owned());
+    value["success"] = Value::Bool(true);
+    value
+}
 
-Options:
-    -h, --help               Print help information"
+// With a previous status, status transitions and CPU/memory deltas are shown (in colour if styled).
+fn status_lines(
+    info: &GetServerResponse,
+    previous: Option<&GetServerResponse>,
+    styled: bool,
+) -> Vec<String> {
+    let mut status = format!(
+        "Status: {}{}",
+        parse_status(info.status),
+        parse_to_delete(info.to_delete)
     );
+    if let Some(previous) = previous.filter(|previous| previous.status != info.status) {
+        status = format!("{} (was {})", status, parse_status(previous.status));
+        if styled {
+            status = match info.status {
+                STATUS_ONLINE => status.green().bold().to_string(),
+                _ => status.red().bold().to_string(),
+            };
+        }
+    }
+
+    let mut cpu = format!("CPU usage: {:.2}%", info.cpu_usage);
+    if let Some(previous) = previous {
+        let delta = info.cpu_usage - previous.cpu_usage;
+        cpu.push_str(&format_delta(format!("{:+.2}%", delta), delta, styled));
+    }
+
+    let memory_usage = info.memory_usage as f64 / 1024.0 / 1024.0;
+    let total_memory = info.total_memory as f64 / 1024.0 / 1024.0;
+    let mut memory = match memory_percentage(info) {
+        Some(percentage) => format!(
+            "Memory usage: {:.2}% ({:.2} MB / {:.0} MB)",
+            percentage, memory_usage, total_memory
+        ),
+        None => format!("Memory usage: {:.2} MB", memory_usage),
+    };
+    if let Some(previous) = previous {
+        let delta = (info.memory_usage - previous.memory_usage) as f64 / 1024.0 / 1024.0;
+        memory.push_str(&format_delta(format!("{:+.2} MB", delta), delta, styled));
+    }
+
+    vec![
+        status,
+        cpu,
+        memory,
+        format!("Uptime: {}", parse_duration(info.uptime)),
+    ]
+}
+
+fn format_delta(text: String, delta: f64, styled: bool) -> String {
+    let text = format!(" ({})", text);
+    if !styled || delta.abs() < 0.005 {
+        return text;
+    }
+    match delta > 0.0 {
+        true => text.yellow().to_string(),
+        false => text.cyan().to_string(),
+    }
+}
+
+// Names are sorted ascending, everything else descending (i.e. the heaviest apps first).
+fn sort_apps(apps: &mut [(String, GetServerResponse)], sort: &str) {
+    apps.sort_by(|(a_name, a), (b_name, b)| match sort {
+        "cpu" => b.cpu_usage.total_cmp(&a.cpu_usage),
+        "memory" => b.memory_usage.cmp(&a.memory_usage),
+        "uptime" => b.uptime.cmp(&a.uptime),
+        _ => a_name.cmp(b_name),
+    });
 }
 
+fn print_table(apps: &[(String, GetServerResponse)]) {
+    let rows = apps
+        .iter()
+        .map(|(name, info)| {
+            let memory_usage = info.memory_usage as f64 / 1024.0 / 1024.0;
+            let memory = match memory_percentage(info) {
+                Some(percentage) => format!("{:.2} MB ({:.2}%)", memory_usage, percentage),
+                None => format!("{:.2} MB", memory_usage),
+            };
+            [
+                name.clone(),
+                parse_status(info.status),
+                format!("{:.2}%", info.cpu_usage),
+                memory,
+                short_duration(info.uptime),
+                if info.to_delete { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect::<Vec<[String; 6]>>();
+    let headers = ["NAME", "STATUS", "CPU", "MEMORY", "UPTIME", "TO DELETE"];
+    let mut widths = headers.map(|header| header.len());
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_owned()
+    };
+    println!("{}", format_row(headers.to_vec()));
+    for row in rows.iter() {
+        println!(
+            "{}",
+            format_row(row.iter().map(|cell| cell.as_str()).collect())
+        );
+    }
+}
+
+pub const COMMAND: Command = Command::new("status", "Get the status of apps")
+    .description(
+        "
+If a single app is specified, its status is shown in detail. If multiple apps
+are specified (or --all is used), the status of all of them is fetched
+concurrently and shown in a table, which can be sorted using --sort.
+
+With --watch, the status of a single app is refreshed every 2 seconds (or the
+specified number of seconds), highlighting status changes and CPU and memory
+usage deltas. With --fail-if-down, octynectl exits with exit code 2 as soon as
+the app is not online, or with the exit code of the error if its status can't
+be fetched (e.g. 6 if the app was deleted).",
+    )
+    .aliases(&["info"])
+    .args("[APP NAMES...]", 0, None)
+    .completes(ArgKind::Apps)
+    .options(&[
+        Opt::flag("all", "Show the status of all apps").short('a'),
+        Opt::value("sort", "column", "Column to sort the table of apps by")
+            .choices(&SORTS)
+            .default("name"),
+        Opt::value("format", "format", "Format to print the table of apps in")
+            .choices(&FORMATS)
+            .default("table"),
+        Opt::value("watch", "seconds", "Keep refreshing the status of the app")
+            .number()
+            .optional()
+            .short('w'),
+        Opt::flag(
+            "fail-if-down",
+            "With --watch, exit with a non-zero exit code once
+the app is not online",
+        ),
+    ]);
+
 // Taken from https://github.com/retrixe/ecthelion statistics page /dashboard/[server]
 fn parse_duration(duration_nano: i64) -> String {
     let duration = duration_nano as f64 / 1000000.0; // Convert to milliseconds
@@ -96,7 +417,27 @@ fn parse_duration(duration_nano: i64) -> String {
     res.trim_end().to_owned()
 }
 
-fn parse_status(status: i32) -> String {
+// e.g. 2d 3h 4m, for tables where parse_duration would be too wide.
+pub fn short_duration(duration_nano: i64) -> String {
+    let seconds = duration_nano / 1_000_000_000;
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+// The total memory is 0 if Octyne couldn't determine it, in which case there's no percentage.
+fn memory_percentage(info: &GetServerResponse) -> Option<f64> {
+    (info.total_memory > 0).then(|| info.memory_usage as f64 / info.total_memory as f64 * 100.0)
+}
+
+pub fn parse_status(status: i32) -> String {
     match status {
         0 => "Offline".to_string(),
         1 => "Online".to_string(),
@@ -1,47 +1,43 @@
-use std::{collections::HashMap, process::exit};
+use std::collections::HashMap;
 
-use crate::api::server::{post_server, PostServerAction};
+use super::wait::{TIMEOUT_OPTION, WAIT_OPTION};
+use crate::utils::actions::run_action;
+use crate::utils::announce::{announce, countdown, ANNOUNCE_OPTION, COUNTDOWN_OPTION};
+use crate::utils::cli::{ArgKind, Command};
+use crate::utils::config::Node;
+use crate::utils::misc::PARALLEL_OPTION;
+use crate::utils::selector::{
+    select_apps, ALL_OPTION, DRY_RUN_OPTION, EXCEPT_OPTION, SELECTOR_HELP, STATUS_OPTION,
+};
+use octynectl::api::server::{PostServerAction, STATUS_CRASHED, STATUS_OFFLINE};
 
-pub async fn stop_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        stop_cmd_help();
-        return;
-    } else if args.len() < 2 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "stop")
-        );
-        exit(1);
-    }
-
-    let mut any_errored = false;
-    for server_name in args[1..].iter() {
-        match post_server(server_name.to_string(), PostServerAction::Term).await {
-            Ok(_) => {}
-            Err(e) => {
-                println!("Error stopping {}: {}", server_name, e);
-                any_errored = true;
-            }
-        }
-    }
-    if any_errored {
-        exit(1);
-    }
+pub async fn stop_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let marks = countdown(&opts, "stop");
+    let args = select_apps(args, &opts, &node, "stop").await;
+    announce(&args, &marks, &opts, &node).await;
+    run_action(
+        &args,
+        &opts,
+        &node,
+        PostServerAction::Term,
+        "stopping",
+        &[STATUS_OFFLINE, STATUS_CRASHED],
+    )
+    .await;
 }
 
-pub fn stop_cmd_help() {
-    println!(
-        "Gracefully stop an app managed by Octyne.
-
-Usage: octynectl stop [OPTIONS] [APP NAMES...]
-
-Options:
-    -h, --help               Print help information"
-    );
-}
+pub const COMMAND: Command = Command::new("stop", "Gracefully stop an app managed by Octyne")
+    .args("[APP NAMES...]", 0, None)
+    .description(SELECTOR_HELP)
+    .completes(ArgKind::Apps)
+    .options(&[
+        ALL_OPTION,
+        STATUS_OPTION,
+        EXCEPT_OPTION,
+        DRY_RUN_OPTION,
+        ANNOUNCE_OPTION,
+        COUNTDOWN_OPTION,
+        PARALLEL_OPTION,
+        WAIT_OPTION,
+        TIMEOUT_OPTION,
+    ]);
@@ -0,0 +1,395 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{stdout, Write},
+    panic,
+    process::exit,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+    tty::IsTty,
+};
+use futures_util::{future::join_all, StreamExt};
+use tokio::{
+    select,
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+};
+
+use super::restart::restart_app;
+use super::status::{parse_status, short_duration};
+use crate::help::invalid_usage;
+use crate::utils::cli::{Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::output::Output;
+use octynectl::api::common::ApiError;
+use octynectl::api::server::{get_server, post_server, GetServerResponse, PostServerAction};
+use octynectl::api::servers::{get_servers, ServerExtraInfo};
+
+const HISTORY: usize = 20;
+
+// Result of restarting an app in the background, along with its name.
+type Restarted = (String, Result<(), (String, ApiError)>);
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const KEYS: &str = "s start  x stop  K kill  r restart  c console  ↑/↓ select  q quit";
+
+struct App {
+    name: String,
+    info: Option<GetServerResponse>,
+    to_delete: bool,
+    cpu: VecDeque<f64>,
+    memory: VecDeque<f64>,
+}
+
+struct Dashboard {
+    apps: Vec<App>,
+    selected: usize,
+    message: String,
+}
+
+enum Action {
+    Post(PostServerAction),
+    Restart,
+    Console,
+    Quit,
+}
+
+pub async fn top_cmd(_args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    if output.is_json() {
+        output.fail("Error", "top does not support --output=json!", 1);
+    } else if !stdout().is_tty() {
+        output.fail("Error", "top can only be used in a terminal!", 1);
+    }
+    let seconds = opts
+        .get("interval")
+        .map_or(2, |interval| interval.parse::<u64>().unwrap_or_default());
+    if seconds == 0 {
+        output.fail_usage(invalid_usage("--interval must be at least 1 second", "top"));
+    }
+
+    let mut dashboard = Dashboard {
+        apps: Vec::new(),
+        selected: 0,
+        message: String::new(),
+    };
+    if let Err(e) = refresh(&mut dashboard, &node).await {
+        output.fail_api("Error", e);
+    }
+
+    enter_screen();
+    let mut events = EventStream::new();
+    let (restarted_tx, mut restarted_rx) = unbounded_channel::<Restarted>();
+    let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+    interval.tick().await;
+    let console_app = loop {
+        draw(&dashboard, &node, seconds);
+        select! {
+            _ = interval.tick() => {
+                if let Err(e) = refresh(&mut dashboard, &node).await {
+                    dashboard.message = format!("Error refreshing: {}", e);
+                }
+            }
+            Some((name, result)) = restarted_rx.recv() => {
+                dashboard.message = match result {
+                    Ok(_) => format!("Restarted {}", name),
+                    Err((context, e)) => format!("{}: {}", context, e),
+                };
+                if let Err(e) = refresh(&mut dashboard, &node).await {
+                    dashboard.message = format!("Error refreshing: {}", e);
+                }
+            }
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    match handle_key(&mut dashboard, key) {
+                        Some(Action::Quit) => break None,
+                        Some(Action::Console) => {
+                            break dashboard.apps.get(dashboard.selected).map(|app| app.name.clone())
+                        }
+                        Some(action) => {
+                            perform(&mut dashboard, &node, action, &restarted_tx).await;
+                            if let Err(e) = refresh(&mut dashboard, &node).await {
+                                dashboard.message = format!("Error refreshing: {}", e);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    leave_screen();
+                    output.fail("Error", format!("Failed to read input! {}", e), 1);
+                }
+                None => break None,
+            },
+        }
+    };
+    leave_screen();
+
+    // The console takes over the terminal, so top exits once it's opened.
+    if let Some(name) = console_app {
+        super::console::console_cmd(vec![name], opts, node).await;
+    }
+}
+
+async fn refresh(dashboard: &mut Dashboard, node: &Node) -> Result<(), ApiError> {
+    let servers = get_servers(&node.client, true).await?;
+    let names = servers.keys().cloned().collect::<Vec<String>>();
+    let responses = join_all(
+        names
+            .iter()
+            .map(|name| get_server(&node.client, name.clone())),
+    )
+    .await;
+
+    let selected = dashboard
+        .apps
+        .get(dashboard.selected)
+        .map(|app| app.name.clone());
+    let mut apps = Vec::new();
+    for ((name, value), response) in servers.into_iter().zip(responses) {
+        let mut app = match dashboard.apps.iter().position(|app| app.name == name) {
+            Some(index) => dashboard.apps.swap_remove(index),
+            None => App {
+                name: name.clone(),
+                info: None,
+                to_delete: false,
+                cpu: VecDeque::new(),
+                memory: VecDeque::new(),
+            },
+        };
+        app.to_delete = ServerExtraInfo::from_value(value).to_delete;
+        app.info = response.ok();
+        let (cpu, memory) = app.info.as_ref().map_or((0.0, 0.0), |info| {
+            (info.cpu_usage, info.memory_usage as f64)
+        });
+        push_history(&mut app.cpu, cpu);
+        push_history(&mut app.memory, memory);
+        apps.push(app);
+    }
+    dashboard.apps = apps;
+    dashboard.selected = selected
+        .and_then(|name| dashboard.apps.iter().position(|app| app.name == name))
+        .unwrap_or(0)
+        .min(dashboard.apps.len().saturating_sub(1));
+    Ok(())
+}
+
+fn push_history(history: &mut VecDeque<f64>, value: f64) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+fn handle_key(dashboard: &mut Dashboard, key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        KeyCode::Up | KeyCode::Char('k') => {
+            dashboard.selected = dashboard.selected.saturating_sub(1);
+            None
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if dashboard.selected + 1 < dashboard.apps.len() {
+                dashboard.selected += 1;
+            }
+            None
+        }
+        KeyCode::Char('s') => Some(Action::Post(PostServerAction::Start)),
+        KeyCode::Char('x') => Some(Action::Post(PostServerAction::Term)),
+        KeyCode::Char('K') => Some(Action::Post(PostServerAction::Stop)),
+        KeyCode::Char('r') => Some(Action::Restart),
+        KeyCode::Char('c') | KeyCode::Enter => Some(Action::Console),
+        _ => None,
+    }
+}
+
+async fn perform(
+    dashboard: &mut Dashboard,
+    node: &Node,
+    action: Action,
+    restarted: &UnboundedSender<Restarted>,
+) {
+    let Some(app) = dashboard.apps.get(dashboard.selected) else {
+        return;
+    };
+    let name = app.name.clone();
+    let result = match &action {
+        Action::Post(action) => post_server(&node.client, name.clone(), action.clone()).await,
+        // Restarting waits for the app to stop like `restart` does, which can take a while, so it
+        // happens in the background while the dashboard keeps updating.
+        Action::Restart => {
+            let (name, node, restarted) = (name.clone(), node.clone(), restarted.clone());
+            tokio::spawn(async move {
+                let result = restart_app(&name, &HashMap::new(), &node, false).await;
+                restarted.send((name, result)).unwrap_or_default();
+            });
+            Ok(())
+        }
+        _ => return,
+    };
+    let verb = match action {
+        Action::Post(PostServerAction::Start) => "start",
+        Action::Post(PostServerAction::Term) => "stop",
+        Action::Post(PostServerAction::Stop) => "kill",
+        _ => "restart",
+    };
+    dashboard.message = match result {
+        Ok(_) => format!("Requested {} of {}", verb, name),
+        Err(e) => format!("Error: Failed to {} {}: {}", verb, name, e),
+    };
+}
+
+// Scaled to the ceiling (e.g. 100% CPU or the total memory), unless a value exceeds it.
+fn sparkline(history: &VecDeque<f64>, ceiling: f64) -> String {
+    let max = history.iter().cloned().fold(ceiling, f64::max);
+    let line = history
+        .iter()
+        .map(|value| match max > 0.0 {
+            true => SPARKS[((value / max) * (SPARKS.len() - 1) as f64).round() as usize],
+            false => SPARKS[0],
+        })
+        .collect::<String>();
+    format!("{:width$}", line, width = HISTORY)
+}
+
+fn draw(dashboard: &Dashboard, node: &Node, seconds: u64) {
+    let (width, height) = terminal::size().unwrap_or((80, 24));
+    let mut lines = vec![
+        format!(
+            "octynectl top - {} - refreshing every {}s",
+            node.description(),
+            seconds
+        ),
+        String::new(),
+    ];
+    let name_width = dashboard
+        .apps
+        .iter()
+        .map(|app| app.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    lines.push(format!(
+        "  {:name_width$}  {:8}  {:>7}  {:HISTORY$}  {:>10}  {:HISTORY$}  UPTIME",
+        "NAME", "STATUS", "CPU", "CPU HISTORY", "MEMORY", "MEMORY HISTORY",
+    ));
+    let rows = (height as usize).saturating_sub(6);
+    let skip = (dashboard.selected + 1).saturating_sub(rows);
+    for app in dashboard.apps.iter().skip(skip).take(rows) {
+        let (status, cpu, memory, uptime) = match &app.info {
+            Some(info) => (
+                parse_status(info.status),
+                format!("{:.2}%", info.cpu_usage),
+                format!("{:.2} MB", info.memory_usage as f64 / 1024.0 / 1024.0),
+                short_duration(info.uptime),
+            ),
+            None => ("Unknown".to_string(), "-".into(), "-".into(), "-".into()),
+        };
+        let marker = if app.to_delete { "*" } else { " " };
+        let total_memory = app
+            .info
+            .as_ref()
+            .map_or(0.0, |info| info.total_memory as f64);
+        lines.push(format!(
+            " {}{:name_width$}  {:8}  {:>7}  {}  {:>10}  {}  {}",
+            marker,
+            app.name,
+            status,
+            cpu,
+            sparkline(&app.cpu, 100.0),
+            memory,
+            sparkline(&app.memory, total_memory),
+            uptime,
+        ));
+    }
+    if dashboard.apps.is_empty() {
+        lines.push(format!(
+            "  No apps are running under {}.",
+            node.description()
+        ));
+    }
+
+    let mut stdout = stdout();
+    queue!(stdout, cursor::MoveTo(0, 0)).unwrap_or_default();
+    for (index, line) in lines.iter().enumerate() {
+        let line = line.chars().take(width as usize).collect::<String>();
+        let selected = index >= 3 && index - 3 + skip == dashboard.selected;
+        if selected {
+            queue!(stdout, SetAttribute(Attribute::Reverse)).unwrap_or_default();
+        }
+        queue!(
+            stdout,
+            Print(format!("{:width$}", line, width = width as usize)),
+            SetAttribute(Attribute::Reset),
+            cursor::MoveToNextLine(1),
+        )
+        .unwrap_or_default();
+    }
+    queue!(
+        stdout,
+        terminal::Clear(ClearType::FromCursorDown),
+        cursor::MoveTo(0, height.saturating_sub(2)),
+        Print(
+            dashboard
+                .message
+                .chars()
+                .take(width as usize)
+                .collect::<String>()
+        ),
+        cursor::MoveTo(0, height.saturating_sub(1)),
+        SetAttribute(Attribute::Dim),
+        Print(KEYS.chars().take(width as usize).collect::<String>()),
+        SetAttribute(Attribute::Reset),
+    )
+    .unwrap_or_default();
+    stdout.flush().unwrap_or_default();
+}
+
+fn enter_screen() {
+    terminal::enable_raw_mode().unwrap_or_else(|e| {
+        println!("Error: Failed to set up terminal! {}", e);
+        exit(1);
+    });
+    execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide).unwrap_or_default();
+    // Restore the terminal before the panic message is printed, else it's left unusable.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        leave_screen();
+        hook(info);
+    }));
+}
+
+fn leave_screen() {
+    execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen).unwrap_or_default();
+    terminal::disable_raw_mode().unwrap_or_default();
+}
+
+pub const COMMAND: Command = Command::new("top", "Show a live dashboard of all apps")
+    .description(
+        "
+This opens a full-screen dashboard showing the status, CPU usage, memory usage
+and uptime of every app, along with their recent history, refreshed every few
+seconds. Apps marked for deletion are marked with a *.
+
+Keybindings:
+    Up/Down, k/j    Select an app
+    s               Start the selected app
+    x               Gracefully stop the selected app
+    K               Kill the selected app
+    r               Restart the selected app
+    c, Enter        Open the selected app's console (leaving the dashboard)
+    q, Esc          Quit",
+    )
+    .options(&[
+        Opt::value("interval", "seconds", "Number of seconds between refreshes")
+            .number()
+            .default("2"),
+    ]);
@@ -1,27 +1,29 @@
-use std::{collections::HashMap, process::exit};
+use std::collections::HashMap;
 
-use crate::api::version::get_version;
+use serde_json::json;
 
-pub async fn version_cmd(args: Vec<String>, top_level_opts: HashMap<String, String>) {
-    let mut args = args.clone();
-    let opts = crate::utils::options::parse_options(&mut args, false);
-    if top_level_opts.contains_key("h")
-        || top_level_opts.contains_key("help")
-        || opts.contains_key("h")
-        || opts.contains_key("help")
-    {
-        version_cmd_help();
-        return;
-    } else if args.len() != 1 {
-        println!(
-            "{}",
-            crate::help::invalid_usage(crate::help::INCORRECT_USAGE, "version")
-        );
-        exit(1);
+use crate::utils::cli::Command;
+use crate::utils::config::Node;
+use crate::utils::output::{print_json, Output};
+use octynectl::api::version::get_version;
+
+pub async fn version_cmd(_args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let version = get_version(&node.client).await;
+    if Output::from_opts(&opts).is_json() {
+        let (octyne, error) = match version {
+            Ok(version) => (Some(version), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        return print_json(&json!({
+            "success": error.is_none(),
+            "octynectl": env!("CARGO_PKG_VERSION"),
+            "octyne": octyne,
+            "error": error,
+        }));
     }
 
     println!("octynectl version {}", env!("CARGO_PKG_VERSION"));
-    match get_version().await {
+    match version {
         Ok(version) => {
             println!("octyne version {}", version);
         }
@@ -31,15 +33,4 @@ pub async fn version_cmd(args: Vec<String>, top_level_opts: HashMap<String, Stri
     };
 }
 
-pub fn version_cmd_help() {
-    println!(
-        "Get the version of octynectl and octyne.
-
-Usage: octynectl version [OPTIONS]
-
-Aliases: info
-
-Options:
-    -h, --help               Print help information"
-    );
-}
+pub const COMMAND: Command = Command::new("version", "Get the version of octynectl and octyne");
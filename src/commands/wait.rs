@@ -0,0 +1,96 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde_json::json;
+
+use crate::utils::cli::{ArgKind, Command, Opt};
+use crate::utils::config::Node;
+use crate::utils::misc::parse_duration;
+use crate::utils::output::{print_json, Output};
+use octynectl::api::common::ApiError;
+use octynectl::api::server::{wait_for_status, STATUS_CRASHED, STATUS_OFFLINE, STATUS_ONLINE};
+
+pub const STATES: [&str; 3] = ["online", "offline", "crashed"];
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub const WAIT_OPTION: Opt = Opt::flag(
+    "wait",
+    "Wait for the app to reach the expected state
+before exiting",
+);
+
+pub const TIMEOUT_OPTION: Opt = Opt::value(
+    "timeout",
+    "duration",
+    "Maximum time to wait for (e.g. 30s, 5m), after
+which octynectl exits with exit code 9",
+)
+.duration()
+.default("60s");
+
+pub async fn wait_cmd(args: Vec<String>, opts: HashMap<String, String>, node: Node) {
+    let output = Output::from_opts(&opts);
+    let state = opts.get("for").map_or("online", |state| state.as_str());
+    let status = match state {
+        "offline" => STATUS_OFFLINE,
+        "crashed" => STATUS_CRASHED,
+        _ => STATUS_ONLINE,
+    };
+    match wait_for_status(
+        &node.client,
+        args[0].clone(),
+        &[status],
+        timeout(&opts),
+        POLL_INTERVAL,
+    )
+    .await
+    {
+        Ok(_) if output.is_json() => print_json(&json!({ "success": true, "state": state })),
+        Ok(_) => {}
+        Err(e) => output.fail_api("Error", e),
+    }
+}
+
+pub fn timeout(opts: &HashMap<String, String>) -> Duration {
+    opts.get("timeout")
+        .and_then(|timeout| parse_duration(timeout).ok())
+        .unwrap_or(Duration::from_secs(60))
+}
+
+// With --wait, waits for an app an action succeeded on to reach one of the given statuses.
+pub async fn wait_for_app(
+    name: &str,
+    statuses: &[i32],
+    opts: &HashMap<String, String>,
+    node: &Node,
+) -> Result<(), (String, ApiError)> {
+    if !opts.contains_key("wait") {
+        return Ok(());
+    }
+    wait_for_status(
+        &node.client,
+        name.to_owned(),
+        statuses,
+        timeout(opts),
+        POLL_INTERVAL,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|e| (format!("Error waiting for {}", name), e))
+}
+
+pub const COMMAND: Command = Command::new("wait", "Wait for an app to reach a state")
+    .description(
+        "
+Polls the status of the app until it is online (or offline/crashed with --for),
+which is useful in scripts e.g. after starting an app. If the app does not reach
+the state within the timeout, octynectl exits with exit code 9.",
+    )
+    .args("[APP NAME]", 1, Some(1))
+    .completes(ArgKind::Apps)
+    .options(&[
+        Opt::value("for", "state", "State to wait for the app to reach")
+            .choices(&STATES)
+            .default("online"),
+        TIMEOUT_OPTION,
+    ]);
@@ -1,5 +1,9 @@
 use std::process::exit;
 
+use crate::commands;
+use crate::utils::cli::{self, ArgKind, Command, Opt};
+use crate::utils::output::OUTPUTS;
+
 pub const USAGE: &str = "{0}, run `octynectl help{1}` for more information.";
 
 pub const INCORRECT_USAGE: &str = "Incorrect usage";
@@ -32,126 +36,114 @@ pub fn invalid_usage_str(msg: String, subcommand: String) -> String {
         .replace("{1}", (" ".to_owned() + &subcommand).as_str())
 }
 
-pub fn log_too_many_args(subcommand: String) {
-    println!(
-        "{}",
-        invalid_usage_str(TOO_MANY_ARGS.to_string(), subcommand)
-    );
-}
-
-// TODO: eventually have `nodes` and --node=NAME
-pub const HELP_STR: &str = "Command-line interface to control Octyne.
-This connects to your local Octyne instance over Unix socket, and lets you view
-and control applications running under it.
+pub const ROOT: Command = Command::new("octynectl", "Command-line interface to control Octyne")
+    .description(
+        "This connects to your local Octyne instance over Unix socket (or to a remote
+Octyne instance over HTTP(S)), and lets you view and control applications
+running under it.
 
-Usage: octynectl [OPTIONS] [SUBCOMMAND]
+Exit codes:
+    0    Success
+    1    Incorrect usage, or any other error not listed below
+    2    Octyne returned an error, or failed to perform the action
+    3    The Unix socket of the Octyne instance does not exist
+    4    Permission denied when accessing the Unix socket
+    5    The Octyne instance could not be reached
+    6    The app does not exist
+    7    Authentication with Octyne failed
+    8    Octyne sent a corrupt or unexpected response
+    9    Timed out waiting for an app to reach a state",
+    )
+    .options(&[
+        Opt::flag("version", "Print version info and exit").short('v'),
+        Opt::flag("help", "Print help information").short('h'),
+        Opt::value(
+            "socket",
+            "path",
+            "Path to the Unix socket of the Octyne instance
+to connect to (overrides $OCTYNE_SOCKET)",
+        ),
+        Opt::value(
+            "port",
+            "port",
+            "Port of the Octyne instance to connect to, used
+to find its Unix socket",
+        )
+        .number()
+        .default("42069"),
+        Opt::value(
+            "url",
+            "url",
+            "URL of a remote Octyne instance to connect to
+over HTTP(S) (overrides $OCTYNE_URL)",
+        ),
+        Opt::value(
+            "node",
+            "name",
+            "Name of a node configured using `octynectl nodes`
+to connect to (overrides $OCTYNE_NODE)",
+        ),
+        Opt::value(
+            "output",
+            "format",
+            "Print the result of the command as text or as
+a JSON object, for use in scripts",
+        )
+        .choices(&OUTPUTS)
+        .default("text"),
+    ])
+    .subcommands(&[
+        commands::list::COMMAND,
+        commands::start::COMMAND,
+        commands::stop::COMMAND,
+        commands::kill::COMMAND,
+        commands::restart::COMMAND,
+        commands::rolling_restart::COMMAND,
+        commands::status::COMMAND,
+        commands::top::COMMAND,
+        commands::logs::COMMAND,
+        commands::console::COMMAND,
+        commands::exec::COMMAND,
+        commands::config::COMMAND,
+        commands::accounts::COMMAND,
+        commands::nodes::COMMAND,
+        commands::login::COMMAND,
+        commands::logout::COMMAND,
+        commands::wait::COMMAND,
+        commands::version::COMMAND,
+        commands::completions::COMMAND,
+        commands::man::COMMAND,
+        commands::completions::COMPLETE_COMMAND,
+        HELP_COMMAND,
+    ]);
 
-Options:
-    -v, --version            Print version info and exit
-    -h, --help               Print help information
-
-Subcommands:
-    list, list-apps, apps    List all apps under Octyne
-    start                    Start an app managed by Octyne
-    stop                     Gracefully stop an app managed by Octyne
-    kill                     Kill an app managed by Octyne
-    restart                  Restart an app
-    status                   Get the status of an app
-    logs                     Get the output logs of an app
-    console                  Interact with an app's console and send input
-    config                   Edit/view/reload Octyne's config (`help config`)
-    account(s), user(s)      Manage Octyne accounts (`help accounts`)
-    version                  Get the version of Octyne and octynectl
-    help                     Print this help message and exit
-";
+pub const HELP_COMMAND: Command = Command::new("help", "Print help information for a subcommand")
+    .args("(SUBCOMMAND...)", 0, None)
+    .completes(ArgKind::Subcommands);
 
 pub fn help_cmd(args: Vec<String>) {
-    if args.len() > 1 {
-        let subcommand_tmp = args[1].clone();
-        let subcommand = subcommand_tmp.as_str();
-        let first_level: Vec<(&str, fn())> = vec![
-            ("list,list-apps,apps", crate::commands::list::list_cmd_help),
-            ("start", crate::commands::start::start_cmd_help),
-            ("stop", crate::commands::stop::stop_cmd_help),
-            ("kill", crate::commands::kill::kill_cmd_help),
-            ("restart", crate::commands::restart::restart_cmd_help),
-            ("status,info", crate::commands::status::status_cmd_help),
-            ("logs", crate::commands::logs::logs_cmd_help),
-            ("console", crate::commands::console::console_cmd_help),
-            ("config", crate::commands::config::config_cmd_help),
-            (
-                "account,accounts",
-                crate::commands::accounts::accounts_cmd_help,
-            ),
-            ("version", crate::commands::version::version_cmd_help),
-        ];
-        if args.len() == 2 {
-            for (aliases, help) in first_level.iter() {
-                for alias in aliases.split(',') {
-                    if subcommand == alias {
-                        return help();
-                    }
-                }
-            }
-            println!(
-                "{}",
-                invalid_usage(unknown_subcommand(subcommand).as_str(), "")
-            );
-            exit(1);
-        }
-        // For subsequent levels, we will still rely on custom logic for now.
-        match subcommand {
-            "config" => {
-                if args.len() > 3 {
-                    log_too_many_args(args[1].clone());
-                } else if args[2] == "view" || args[2] == "show" {
-                    crate::commands::config::config_view_cmd_help();
-                } else if args[2] == "edit" || args[2] == "modify" {
-                    crate::commands::config::config_edit_cmd_help();
-                } else if args[2] == "reload" {
-                    crate::commands::config::config_reload_cmd_help();
-                } else {
-                    println!(
-                        "{}",
-                        invalid_usage_str(
-                            unknown_subcommand_str(subcommand.to_owned() + " " + &args[2]),
-                            args[1].clone()
-                        )
-                    );
-                }
-            }
-            "account" | "accounts" => {
-                if args.len() > 3 {
-                    log_too_many_args(args[1].clone());
-                } else if args[2] == "list" || args[2] == "show" {
-                    crate::commands::accounts::accounts_list_cmd_help();
-                } else if args[2] == "create" || args[2] == "add" {
-                    crate::commands::accounts::accounts_create_cmd_help();
-                } else if args[2] == "delete" || args[2] == "remove" {
-                    crate::commands::accounts::accounts_delete_cmd_help();
-                } else if args[2] == "rename" {
-                    crate::commands::accounts::accounts_rename_cmd_help();
-                } else if args[2] == "passwd" {
-                    crate::commands::accounts::accounts_passwd_cmd_help();
-                } else {
-                    println!(
-                        "{}",
-                        invalid_usage_str(
-                            unknown_subcommand_str(subcommand.to_owned() + " " + &args[2]),
-                            args[1].clone()
-                        )
-                    );
-                }
+    let mut path = vec![&ROOT];
+    for (index, name) in args.iter().enumerate() {
+        match path[path.len() - 1].find_subcommand(name) {
+            Some(subcommand) => path.push(subcommand),
+            None if path.len() > 1 && path[path.len() - 1].subcommands.is_empty() => {
+                println!(
+                    "{}",
+                    invalid_usage_str(TOO_MANY_ARGS.to_string(), cli::full_name(&path))
+                );
+                exit(1);
             }
-            _ => {
+            None => {
                 println!(
                     "{}",
-                    invalid_usage(unknown_subcommand(subcommand).as_str(), "")
+                    invalid_usage_str(
+                        unknown_subcommand_str(args[..=index].join(" ")),
+                        cli::full_name(&path)
+                    )
                 );
                 exit(1);
             }
         }
-        return;
     }
-    println!("{}", HELP_STR)
+    println!("{}", cli::help_text(&path));
 }
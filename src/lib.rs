@@ -0,0 +1,22 @@
+//! Library for controlling [Octyne](https://github.com/retrixe/octyne), used by the `octynectl`
+//! command-line interface.
+//!
+//! Octyne can be reached over its Unix socket API (for local instances), or over its HTTP(S) API
+//! (for remote instances), using the [`api::client::OctyneClient`]:
+//!
+//! ```no_run
+//! use octynectl::api::{client::OctyneClient, server, servers, transport::Transport};
+//!
+//! # async fn example() -> Result<(), octynectl::api::common::ApiError> {
+//! let transport = Transport::Unix("/tmp/octyne.sock.42069".into());
+//! let client = OctyneClient::new(transport);
+//! for (name, _) in servers::get_servers(&client, false).await? {
+//!     let info = server::get_server(&client, name.clone()).await?;
+//!     println!("{}: {}% CPU", name, info.cpu_usage);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+#![warn(missing_docs)]
+
+pub mod api;
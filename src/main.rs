@@ -1,61 +1,95 @@
-use std::process::exit;
-
-mod api;
 mod commands;
 mod help;
 mod utils;
 
-// TODO: we need man pages down the line
-// TODO: the opt parser is primitive, only allow certain opts to be parsed, add strings to opt
+use crate::utils::output::Output;
+
 #[tokio::main]
 async fn main() {
     let mut args = std::env::args().collect::<Vec<String>>();
     args.remove(0);
 
-    // Parse top-level options.
-    let top_level_opts = crate::utils::options::parse_options(&mut args, true);
-    if top_level_opts.contains_key("v") || top_level_opts.contains_key("version") {
+    // Parse the command line using the command tree in help.rs.
+    let output = Output::from_args(&args);
+    let matches =
+        crate::utils::cli::parse(&help::ROOT, args).unwrap_or_else(|e| output.fail_usage(e));
+    if matches.opts.contains_key("version") {
         println!("octynectl version {}", env!("CARGO_PKG_VERSION"));
         return;
+    } else if matches.opts.contains_key("help") {
+        println!("{}", crate::utils::cli::help_text(&matches.path));
+        return;
     }
 
     // Check for commands.
-    if args.is_empty() {
-        if top_level_opts.contains_key("h") || top_level_opts.contains_key("help") {
-            println!("{}", help::HELP_STR);
-        } else {
-            println!("{}", help::invalid_usage(help::INCORRECT_USAGE, ""));
-            exit(1);
-        }
+    let names = matches.names();
+    if names.is_empty() {
+        output.fail_usage(help::invalid_usage(help::INCORRECT_USAGE, ""));
+    } else if !matches.command().subcommands.is_empty() {
+        println!("{}", crate::utils::cli::help_text(&matches.path));
         return;
     }
 
+    // Parse subcommands which don't connect to Octyne.
+    let (args, opts) = (matches.args, matches.opts);
+    match names.as_slice() {
+        ["help"] => return crate::help::help_cmd(args),
+        ["completions"] => return crate::commands::completions::completions_cmd(args, opts),
+        ["man"] => return crate::commands::man::man_cmd(args, opts),
+        ["__complete"] => return crate::commands::completions::complete_cmd(args, opts).await,
+        ["nodes", "list"] => return crate::commands::nodes::nodes_list_cmd(args, opts),
+        ["nodes", "add"] => return crate::commands::nodes::nodes_add_cmd(args, opts),
+        ["nodes", "remove"] => return crate::commands::nodes::nodes_remove_cmd(args, opts),
+        ["nodes", "set-default"] => {
+            return crate::commands::nodes::nodes_set_default_cmd(args, opts)
+        }
+        _ => {}
+    }
+
+    // Resolve the Octyne instance to connect to.
+    let node = crate::utils::misc::octyne_node(&opts)
+        .unwrap_or_else(|e| Output::from_opts(&opts).fail("Error", e, 1));
+
     // Parse subcommand.
-    let subcommand_tmp = args[0].clone();
-    let subcommand = subcommand_tmp.as_str();
-    match subcommand {
-        "help" => crate::help::help_cmd(args),
-        "list" | "list-apps" | "apps" => {
-            crate::commands::list::list_cmd(args, top_level_opts).await
-        }
-        "start" => crate::commands::start::start_cmd(args, top_level_opts).await,
-        "stop" => crate::commands::stop::stop_cmd(args, top_level_opts).await,
-        "kill" => crate::commands::kill::kill_cmd(args, top_level_opts).await,
-        "restart" => crate::commands::restart::restart_cmd(args, top_level_opts).await,
-        "status" | "info" => crate::commands::status::status_cmd(args, top_level_opts).await,
-        "logs" => crate::commands::logs::logs_cmd(args, top_level_opts).await,
-        "console" => crate::commands::console::console_cmd(args, top_level_opts).await,
-        "config" => crate::commands::config::config_cmd(args, top_level_opts).await,
-        "account" | "accounts" => {
-            crate::commands::accounts::accounts_cmd(args, top_level_opts).await
-        }
-        "version" => crate::commands::version::version_cmd(args, top_level_opts).await,
+    match names.as_slice() {
+        ["list-apps"] => crate::commands::list::list_cmd(args, opts, node).await,
+        ["start"] => crate::commands::start::start_cmd(args, opts, node).await,
+        ["stop"] => crate::commands::stop::stop_cmd(args, opts, node).await,
+        ["kill"] => crate::commands::kill::kill_cmd(args, opts, node).await,
+        ["restart"] => crate::commands::restart::restart_cmd(args, opts, node).await,
+        ["rolling-restart"] => {
+            crate::commands::rolling_restart::rolling_restart_cmd(args, opts, node).await
+        }
+        ["status"] => crate::commands::status::status_cmd(args, opts, node).await,
+        ["top"] => crate::commands::top::top_cmd(args, opts, node).await,
+        ["logs"] => crate::commands::logs::logs_cmd(args, opts, node).await,
+        ["console"] => crate::commands::console::console_cmd(args, opts, node).await,
+        ["exec"] => crate::commands::exec::exec_cmd(args, opts, node).await,
+        ["config", "view"] => crate::commands::config::config_view_cmd(args, opts, node).await,
+        ["config", "edit"] => crate::commands::config::config_edit_cmd(args, opts, node).await,
+        ["config", "reload"] => crate::commands::config::config_reload_cmd(args, opts, node).await,
+        ["accounts", "list"] => {
+            crate::commands::accounts::accounts_list_cmd(args, opts, node).await
+        }
+        ["accounts", "create"] => {
+            crate::commands::accounts::accounts_create_cmd(args, opts, node).await
+        }
+        ["accounts", "rename"] => {
+            crate::commands::accounts::accounts_rename_cmd(args, opts, node).await
+        }
+        ["accounts", "delete"] => {
+            crate::commands::accounts::accounts_delete_cmd(args, opts, node).await
+        }
+        ["accounts", "passwd"] => {
+            crate::commands::accounts::accounts_passwd_cmd(args, opts, node).await
+        }
+        ["login"] => crate::commands::login::login_cmd(args, opts, node).await,
+        ["logout"] => crate::commands::logout::logout_cmd(args, opts, node).await,
+        ["wait"] => crate::commands::wait::wait_cmd(args, opts, node).await,
+        ["version"] => crate::commands::version::version_cmd(args, opts, node).await,
         _ => {
-            println!(
-                "{}",
-                help::invalid_usage(help::unknown_subcommand(subcommand).as_str(), "")
-            );
-            exit(1);
+            let error = help::unknown_subcommand(&names.join(" "));
+            output.fail_usage(help::invalid_usage(&error, ""))
         }
     }
 }
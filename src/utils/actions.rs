@@ -0,0 +1,56 @@
+use std::{collections::HashMap, future::Future};
+
+use futures_util::{stream, StreamExt};
+
+use crate::commands::wait::wait_for_app;
+use crate::utils::config::Node;
+use crate::utils::misc::parallelism;
+use crate::utils::output::{Output, Results};
+use octynectl::api::common::ApiError;
+use octynectl::api::server::{post_server, PostServerAction};
+
+// Performs the action on each app (one after the other, or several at once with --parallel),
+// then prints the results and exits with the exit code of the last failure, if any. Errors are
+// returned by the action along with their context.
+pub async fn act_on_apps<'a, F, Fut>(apps: &'a [String], opts: &HashMap<String, String>, action: F)
+where
+    F: Fn(&'a str) -> Fut,
+    Fut: Future<Output = Result<(), (String, ApiError)>> + 'a,
+{
+    let output = Output::from_opts(opts);
+    let mut results = Results::new(output).summary(opts.contains_key("parallel"));
+    let responses = stream::iter(apps.iter())
+        .map(|name| action(name))
+        .buffered(parallelism(opts))
+        .collect::<Vec<_>>()
+        .await;
+    for (name, response) in apps.iter().zip(responses) {
+        match response {
+            Ok(_) => results.success(name),
+            Err((context, e)) => results.failure_api(name, &context, e),
+        }
+    }
+    results.finish(None);
+}
+
+// Posts the action to each app, and with --wait, waits for them to reach one of the statuses.
+// The verb describes the action in errors, e.g. "starting".
+pub async fn run_action(
+    apps: &[String],
+    opts: &HashMap<String, String>,
+    node: &Node,
+    action: PostServerAction,
+    verb: &str,
+    statuses: &[i32],
+) {
+    act_on_apps(apps, opts, |name| {
+        let action = action.clone();
+        async move {
+            post_server(&node.client, name.to_owned(), action)
+                .await
+                .map_err(|e| (format!("Error {} {}", verb, name), e))?;
+            wait_for_app(name, statuses, opts, node).await
+        }
+    })
+    .await
+}
@@ -0,0 +1,95 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures_util::{future::join_all, SinkExt};
+use tokio::time::sleep;
+
+use crate::commands::console::{close_console, input_message};
+use crate::help::invalid_usage;
+use crate::utils::cli::Opt;
+use crate::utils::config::Node;
+use crate::utils::misc::{format_duration, parse_duration};
+use crate::utils::output::Output;
+use octynectl::api::common::ApiError;
+use octynectl::api::server::connect_to_server_console_v1_fallback;
+
+pub const ANNOUNCE_OPTION: Opt = Opt::value(
+    "announce",
+    "message",
+    "Input to send to the app's console beforehand,
+{time} is replaced with the time left",
+);
+
+pub const COUNTDOWN_OPTION: Opt = Opt::value(
+    "countdown",
+    "durations",
+    "Times before acting on the app at which to send
+the announcement (e.g. 5m,1m,10s)",
+);
+
+// Parses --countdown into the times to send the announcement at, longest first. Without
+// --countdown, the announcement is sent once, right before acting on the apps.
+pub fn countdown(opts: &HashMap<String, String>, command: &str) -> Vec<Duration> {
+    if !opts.contains_key("announce") {
+        if opts.contains_key("countdown") {
+            let error = invalid_usage("--countdown requires --announce", command);
+            Output::from_opts(opts).fail_usage(error);
+        }
+        return Vec::new();
+    }
+    let Some(countdown) = opts.get("countdown") else {
+        return vec![Duration::ZERO];
+    };
+    let mut marks = countdown
+        .split(',')
+        .map(|mark| parse_duration(mark.trim()))
+        .collect::<Result<Vec<Duration>, String>>()
+        .unwrap_or_else(|e| Output::from_opts(opts).fail_usage(invalid_usage(&e, command)));
+    marks.sort_by(|a, b| b.cmp(a));
+    marks.dedup();
+    marks
+}
+
+// Sends the announcement to the console of every app at each time in the countdown, and returns
+// once the countdown is over. Apps the announcement can't be sent to are still acted on.
+pub async fn announce(
+    apps: &[String],
+    marks: &[Duration],
+    opts: &HashMap<String, String>,
+    node: &Node,
+) {
+    let output = Output::from_opts(opts);
+    let Some(announcement) = opts.get("announce") else {
+        return;
+    };
+    for (index, mark) in marks.iter().enumerate() {
+        let time = match mark.is_zero() {
+            true => "now".to_string(),
+            false => format_duration(*mark),
+        };
+        let message = announcement.replace("{time}", &time);
+        if !output.is_json() {
+            println!("Announcing: {}", message);
+        }
+        let responses = join_all(apps.iter().map(|name| send_input(node, name, &message))).await;
+        for (name, response) in apps.iter().zip(responses) {
+            match response {
+                Err(e) if !output.is_json() => {
+                    println!("Warning: Failed to send announcement to {}: {}", name, e)
+                }
+                _ => {}
+            }
+        }
+        let next = marks.get(index + 1).copied().unwrap_or(Duration::ZERO);
+        sleep(*mark - next).await;
+    }
+}
+
+async fn send_input(node: &Node, name: &str, input: &str) -> Result<(), ApiError> {
+    let (mut socket, v2) =
+        connect_to_server_console_v1_fallback(&node.client, name.to_owned()).await?;
+    socket
+        .send(input_message(input.to_owned(), v2))
+        .await
+        .map_err(|e| ApiError::Protocol(format!("Failed to send input! {}", e)))?;
+    close_console(&mut socket).await
+}
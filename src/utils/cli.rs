@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+
+use crate::help::{invalid_usage_str, unknown_subcommand_str, INCORRECT_USAGE};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueKind {
+    Text,
+    Number,
+    // A number of at least 1, e.g. a count of apps.
+    PositiveNumber,
+    Duration,
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OptValue {
+    Flag,
+    Required(&'static str, ValueKind),
+    // Only accepts a value in the form --opt=value, e.g. --watch or --watch=5.
+    Optional(&'static str, ValueKind),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Opt {
+    pub long: &'static str,
+    pub short: Option<char>,
+    pub value: OptValue,
+    pub help: &'static str,
+    pub default: Option<&'static str>,
+}
+
+impl Opt {
+    pub const fn flag(long: &'static str, help: &'static str) -> Opt {
+        Opt {
+            long,
+            short: None,
+            value: OptValue::Flag,
+            help,
+            default: None,
+        }
+    }
+
+    pub const fn value(long: &'static str, name: &'static str, help: &'static str) -> Opt {
+        Opt {
+            long,
+            short: None,
+            value: OptValue::Required(name, ValueKind::Text),
+            help,
+            default: None,
+        }
+    }
+
+    pub const fn short(mut self, short: char) -> Opt {
+        self.short = Some(short);
+        self
+    }
+
+    pub const fn default(mut self, default: &'static str) -> Opt {
+        self.default = Some(default);
+        self
+    }
+
+    pub const fn number(self) -> Opt {
+        self.kind(ValueKind::Number)
+    }
+
+    pub const fn positive(self) -> Opt {
+        self.kind(ValueKind::PositiveNumber)
+    }
+
+    pub const fn duration(self) -> Opt {
+        self.kind(ValueKind::Duration)
+    }
+
+    pub const fn choices(self, choices: &'static [&'static str]) -> Opt {
+        self.kind(ValueKind::Choice(choices))
+    }
+
+    pub const fn optional(mut self) -> Opt {
+        self.value = match self.value {
+            OptValue::Required(name, kind) => OptValue::Optional(name, kind),
+            value => value,
+        };
+        self
+    }
+
+    const fn kind(mut self, kind: ValueKind) -> Opt {
+        self.value = match self.value {
+            OptValue::Flag => OptValue::Flag,
+            OptValue::Required(name, _) => OptValue::Required(name, kind),
+            OptValue::Optional(name, _) => OptValue::Optional(name, kind),
+        };
+        self
+    }
+
+    pub fn kind_of(&self) -> Option<ValueKind> {
+        match self.value {
+            OptValue::Flag => None,
+            OptValue::Required(_, kind) | OptValue::Optional(_, kind) => Some(kind),
+        }
+    }
+
+    fn validate(&self, value: &str) -> Result<(), String> {
+        let valid = match self.kind_of() {
+            None => return Ok(()),
+            Some(_) if value.is_empty() && matches!(self.value, OptValue::Optional(..)) => true,
+            Some(ValueKind::Text) => !value.is_empty(),
+            Some(ValueKind::Number) => value.parse::<u64>().is_ok(),
+            Some(ValueKind::PositiveNumber) => value.parse::<u64>().is_ok_and(|n| n > 0),
+            Some(ValueKind::Duration) => crate::utils::misc::parse_duration(value).is_ok(),
+            Some(ValueKind::Choice(choices)) => choices.contains(&value),
+        };
+        if valid {
+            return Ok(());
+        } else if let Some(ValueKind::Choice(choices)) = self.kind_of() {
+            return Err(format!(
+                "Invalid value for flag --{} \"{}\"! (Valid values: {})",
+                self.long,
+                value,
+                choices.join(",")
+            ));
+        } else if let Some(ValueKind::PositiveNumber) = self.kind_of() {
+            return Err(format!(
+                "Invalid value for flag --{} \"{}\"! (Must be at least 1)",
+                self.long, value
+            ));
+        }
+        Err(format!(
+            "Invalid value for flag --{} \"{}\"!",
+            self.long, value
+        ))
+    }
+
+    pub fn usage(&self) -> String {
+        let long = match self.value {
+            OptValue::Flag => format!("--{}", self.long),
+            OptValue::Required(name, _) => format!("--{}=<{}>", self.long, name),
+            OptValue::Optional(name, _) => format!("--{}[=<{}>]", self.long, name),
+        };
+        match self.short {
+            Some(short) => format!("-{}, {}", short, long),
+            None => long,
+        }
+    }
+}
+
+// What a command's positional arguments are, used for shell completion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArgKind {
+    None,
+    Apps,
+    Accounts,
+    Nodes,
+    Files,
+    Subcommands,
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Command {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub summary: &'static str,
+    pub description: &'static str,
+    pub usage: &'static str,
+    pub min_args: usize,
+    pub max_args: Option<usize>,
+    pub arg_kind: ArgKind,
+    pub hidden: bool,
+    pub options: &'static [Opt],
+    pub subcommands: &'static [Command],
+}
+
+impl Command {
+    pub const fn new(name: &'static str, summary: &'static str) -> Command {
+        Command {
+            name,
+            aliases: &[],
+            summary,
+            description: "",
+            usage: "",
+            min_args: 0,
+            max_args: Some(0),
+            arg_kind: ArgKind::None,
+            hidden: false,
+            options: &[],
+            subcommands: &[],
+        }
+    }
+
+    pub const fn aliases(mut self, aliases: &'static [&'static str]) -> Command {
+        self.aliases = aliases;
+        self
+    }
+
+    // Shown after the summary in the command's help.
+    pub const fn description(mut self, description: &'static str) -> Command {
+        self.description = description;
+        self
+    }
+
+    // Describes positional arguments, and sets how many are accepted.
+    pub const fn args(mut self, usage: &'static str, min: usize, max: Option<usize>) -> Command {
+        self.usage = usage;
+        self.min_args = min;
+        self.max_args = max;
+        self
+    }
+
+    pub const fn completes(mut self, arg_kind: ArgKind) -> Command {
+        self.arg_kind = arg_kind;
+        self
+    }
+
+    // Hidden commands are left out of help and shell completion.
+    pub const fn hidden(mut self) -> Command {
+        self.hidden = true;
+        self
+    }
+
+    pub const fn options(mut self, options: &'static [Opt]) -> Command {
+        self.options = options;
+        self
+    }
+
+    pub const fn subcommands(mut self, subcommands: &'static [Command]) -> Command {
+        self.subcommands = subcommands;
+        self
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    pub fn find_subcommand(&'static self, name: &str) -> Option<&'static Command> {
+        self.subcommands.iter().find(|cmd| cmd.matches(name))
+    }
+}
+
+pub struct Matches {
+    pub path: Vec<&'static Command>,
+    pub args: Vec<String>,
+    pub opts: HashMap<String, String>,
+}
+
+impl Matches {
+    pub fn command(&self) -> &'static Command {
+        self.path[self.path.len() - 1]
+    }
+
+    // Canonical names of the subcommands, excluding the root command.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.path[1..].iter().map(|cmd| cmd.name).collect()
+    }
+}
+
+pub fn full_name(path: &[&'static Command]) -> String {
+    path[1..]
+        .iter()
+        .map(|cmd| cmd.name)
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+// Options are looked up in the current subcommand first, then the root command (global options).
+pub fn find_option(path: &[&'static Command], long: &str) -> Option<&'static Opt> {
+    let command = path[path.len() - 1];
+    let root = path[0];
+    command
+        .options
+        .iter()
+        .chain(root.options.iter())
+        .find(|opt| opt.long == long)
+}
+
+pub fn find_short_option(path: &[&'static Command], short: char) -> Option<&'static Opt> {
+    let command = path[path.len() - 1];
+    let root = path[0];
+    command
+        .options
+        .iter()
+        .chain(root.options.iter())
+        .find(|opt| opt.short == Some(short))
+}
+
+fn unknown_option(option: String, path: &[&'static Command]) -> String {
+    invalid_usage_str(format!("Unknown option: {}", option), full_name(path))
+}
+
+pub fn parse(root: &'static Command, args: Vec<String>) -> Result<Matches, String> {
+    let mut path = vec![root];
+    let mut positional: Vec<String> = Vec::new();
+    let mut opts: HashMap<String, String> = HashMap::new();
+    let mut only_positional = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let command = path[path.len() - 1];
+        if only_positional || arg == "-" || !arg.starts_with('-') {
+            // The first positional argument of a command with subcommands is the subcommand.
+            if !only_positional && positional.is_empty() && !command.subcommands.is_empty() {
+                match command.find_subcommand(&arg) {
+                    Some(subcommand) => path.push(subcommand),
+                    None => {
+                        let name = (full_name(&path) + " " + &arg).trim().to_owned();
+                        return Err(invalid_usage_str(
+                            unknown_subcommand_str(name),
+                            full_name(&path),
+                        ));
+                    }
+                }
+                continue;
+            }
+            positional.push(arg);
+        } else if arg == "--" {
+            only_positional = true;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (long, None),
+            };
+            let opt = find_option(&path, name).ok_or_else(|| unknown_option(arg.clone(), &path))?;
+            let value = match opt.value {
+                OptValue::Flag if value.is_some() => {
+                    return Err(format!("Error: Flag --{} does not take a value!", opt.long))
+                }
+                OptValue::Flag => String::new(),
+                OptValue::Optional(..) => value.unwrap_or_default(),
+                OptValue::Required(..) => match value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => {
+                        return Err(format!("Error: No value provided for flag --{}!", opt.long))
+                    }
+                },
+            };
+            opt.validate(&value).map_err(|e| format!("Error: {}", e))?;
+            opts.insert(opt.long.to_owned(), value);
+        } else {
+            // Short options can be combined e.g. -abc, and the last one can take a value.
+            let shorts = arg[1..].chars().collect::<Vec<char>>();
+            for (index, short) in shorts.iter().enumerate() {
+                let opt = find_short_option(&path, *short)
+                    .ok_or_else(|| unknown_option(format!("-{}", short), &path))?;
+                let rest = shorts[index + 1..].iter().collect::<String>();
+                let rest = rest.strip_prefix('=').unwrap_or(&rest).to_owned();
+                let value = match opt.value {
+                    OptValue::Flag => String::new(),
+                    OptValue::Required(..) if rest.is_empty() => match args.next() {
+                        Some(value) => value,
+                        None => {
+                            return Err(format!("Error: No value provided for flag -{}!", short))
+                        }
+                    },
+                    OptValue::Required(..) | OptValue::Optional(..) => rest,
+                };
+                opt.validate(&value).map_err(|e| format!("Error: {}", e))?;
+                let takes_value = opt.value != OptValue::Flag;
+                opts.insert(opt.long.to_owned(), value);
+                if takes_value {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Validate the number of positional arguments, unless help or version info was requested.
+    let command = path[path.len() - 1];
+    let info_requested = opts.contains_key("help") || opts.contains_key("version");
+    let takes_subcommand = !command.subcommands.is_empty() && positional.is_empty();
+    if !info_requested
+        && !takes_subcommand
+        && (positional.len() < command.min_args
+            || command.max_args.is_some_and(|max| positional.len() > max))
+    {
+        return Err(invalid_usage_str(
+            INCORRECT_USAGE.to_string(),
+            full_name(&path),
+        ));
+    }
+
+    Ok(Matches {
+        path,
+        args: positional,
+        opts,
+    })
+}
+
+pub fn usage(path: &[&'static Command]) -> String {
+    let command = path[path.len() - 1];
+    let mut usage = ["octynectl", &full_name(path), "[OPTIONS]"]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if !command.subcommands.is_empty() {
+        usage.push_str(" [SUBCOMMAND]");
+    } else if !command.usage.is_empty() {
+        usage.push(' ');
+        usage.push_str(command.usage);
+    }
+    usage
+}
+
+// Options shown in a command's help, subcommands also show the root command's --help option.
+pub fn options(path: &[&'static Command]) -> Vec<&'static Opt> {
+    let command = path[path.len() - 1];
+    let mut options: Vec<&'static Opt> = Vec::new();
+    if path.len() > 1 {
+        options.extend(path[0].options.iter().filter(|opt| opt.long == "help"));
+    }
+    options.extend(command.options.iter());
+    options
+}
+
+pub fn option_help(opt: &Opt) -> String {
+    let mut text = opt.help.to_owned();
+    if let Some(ValueKind::Choice(choices)) = opt.kind_of() {
+        text.push_str(&format!(". Valid values: {}", choices.join(",")));
+    }
+    if let Some(default) = opt.default {
+        text.push_str(&format!("\nDefault: {}", default));
+    }
+    text
+}
+
+fn write_table(help: &mut String, title: &str, rows: Vec<(String, String)>) {
+    if rows.is_empty() {
+        return;
+    }
+    let width = rows
+        .iter()
+        .map(|(left, _)| left.len() + 4)
+        .max()
+        .unwrap_or(0)
+        .max(21);
+    help.push_str(&format!("\n\n{}:", title));
+    for (left, right) in rows {
+        let mut lines = right.lines();
+        let first = lines.next().unwrap_or_default();
+        help.push_str(&format!("\n    {:width$}{}", left, first, width = width));
+        for line in lines {
+            help.push_str(&format!("\n    {:width$}{}", "", line, width = width));
+        }
+    }
+}
+
+pub fn help_text(path: &[&'static Command]) -> String {
+    let command = path[path.len() - 1];
+    let mut help = format!("{}.", command.summary);
+    if !command.description.is_empty() {
+        help.push('\n');
+        help.push_str(command.description);
+    }
+
+    help.push_str(&format!("\n\nUsage: {}", usage(path)));
+
+    if path.len() > 1 && !command.aliases.is_empty() {
+        help.push_str(&format!("\n\nAliases: {}", command.aliases.join(", ")));
+    }
+
+    let subcommands = command
+        .subcommands
+        .iter()
+        .filter(|cmd| !cmd.hidden)
+        .map(|cmd| {
+            let mut names = vec![cmd.name];
+            names.extend(cmd.aliases);
+            (names.join(", "), cmd.summary.to_owned())
+        })
+        .collect();
+    write_table(&mut help, "Subcommands", subcommands);
+
+    let options = options(path)
+        .into_iter()
+        .map(|opt| (opt.usage(), option_help(opt)))
+        .collect();
+    write_table(&mut help, "Options", options);
+    help
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUB: Command = Command::new("sub", "A subcommand")
+        .args("[NAMES...]", 0, None)
+        .options(&[
+            Opt::flag("all", "All").short('a'),
+            Opt::flag("force", "Force").short('f'),
+            Opt::value("timeout", "duration", "Timeout")
+                .short('t')
+                .duration(),
+            Opt::value("count", "n", "Count").number(),
+            Opt::value("watch", "seconds", "Watch").number().optional(),
+            Opt::value("parallel", "count", "Parallel")
+                .positive()
+                .optional(),
+        ]);
+    const ROOT: Command = Command::new("octynectl", "Test")
+        .options(&[Opt::flag("help", "Help").short('h')])
+        .subcommands(&[SUB]);
+
+    fn parse_args(args: &[&str]) -> Result<Matches, String> {
+        parse(&ROOT, args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn parses_subcommand_and_positional_args() {
+        let matches = parse_args(&["sub", "a", "b"]).unwrap();
+        assert_eq!(matches.names(), vec!["sub"]);
+        assert_eq!(matches.args, vec!["a", "b"]);
+        assert!(matches.opts.is_empty());
+    }
+
+    #[test]
+    fn parses_combined_short_options() {
+        let matches = parse_args(&["sub", "-af"]).unwrap();
+        assert!(matches.opts.contains_key("all"));
+        assert!(matches.opts.contains_key("force"));
+
+        let matches = parse_args(&["sub", "-at5s"]).unwrap();
+        assert!(matches.opts.contains_key("all"));
+        assert_eq!(matches.opts["timeout"], "5s");
+
+        let matches = parse_args(&["sub", "-ft", "10", "x"]).unwrap();
+        assert_eq!(matches.opts["timeout"], "10");
+        assert_eq!(matches.args, vec!["x"]);
+    }
+
+    #[test]
+    fn parses_option_values() {
+        let matches = parse_args(&["sub", "--count", "3", "--timeout=1m", "x"]).unwrap();
+        assert_eq!(matches.opts["count"], "3");
+        assert_eq!(matches.opts["timeout"], "1m");
+        assert_eq!(matches.args, vec!["x"]);
+
+        let error = parse_args(&["sub", "--count"]).err().unwrap();
+        assert!(error.contains("No value provided for flag --count"));
+        let error = parse_args(&["sub", "--count=x"]).err().unwrap();
+        assert!(error.contains("Invalid value for flag --count"));
+        let error = parse_args(&["sub", "--all=yes"]).err().unwrap();
+        assert!(error.contains("does not take a value"));
+    }
+
+    #[test]
+    fn parses_optional_values() {
+        let matches = parse_args(&["sub", "--watch", "x"]).unwrap();
+        assert_eq!(matches.opts["watch"], "");
+        assert_eq!(matches.args, vec!["x"]);
+
+        let matches = parse_args(&["sub", "--watch=5"]).unwrap();
+        assert_eq!(matches.opts["watch"], "5");
+
+        let matches = parse_args(&["sub", "--parallel"]).unwrap();
+        assert_eq!(matches.opts["parallel"], "");
+        let error = parse_args(&["sub", "--parallel=0"]).err().unwrap();
+        assert!(error.contains("Invalid value for flag --parallel \"0\"! (Must be at least 1)"));
+    }
+
+    #[test]
+    fn treats_args_after_double_dash_as_positional() {
+        let matches = parse_args(&["sub", "--", "-a", "--force", "sub"]).unwrap();
+        assert!(matches.opts.is_empty());
+        assert_eq!(matches.args, vec!["-a", "--force", "sub"]);
+    }
+
+    #[test]
+    fn finds_global_options_in_subcommands() {
+        let matches = parse_args(&["sub", "-h"]).unwrap();
+        assert!(matches.opts.contains_key("help"));
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_subcommands() {
+        let error = parse_args(&["sub", "--unknown"]).err().unwrap();
+        assert!(error.contains("Unknown option: --unknown"));
+        let error = parse_args(&["sub", "-az"]).err().unwrap();
+        assert!(error.contains("Unknown option: -z"));
+        assert!(parse_args(&["nope"]).is_err());
+    }
+}
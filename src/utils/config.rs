@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use octynectl::api::client::OctyneClient;
+use octynectl::api::transport::Transport;
+
+pub const FORMATS: [&str; 3] = ["json", "csv", "table"];
+
+pub const PAGERS: [&str; 3] = ["auto", "builtin", "none"];
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_node: Option<String>,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, NodeConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    // Name of an environment variable containing the token, else the token saved by `login` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pager: Option<String>,
+}
+
+// An Octyne instance to connect to, along with any preferences from its node profile.
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: Option<String>,
+    pub client: OctyneClient,
+    pub format: Option<String>,
+    pub pager: Option<String>,
+}
+
+impl Node {
+    pub fn new(transport: Transport) -> Node {
+        Node {
+            name: None,
+            client: OctyneClient::new(transport),
+            format: None,
+            pager: None,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match &self.name {
+            Some(name) => format!("the Octyne node `{}`", name),
+            None => "the Octyne instance".to_string(),
+        }
+    }
+}
+
+impl NodeConfig {
+    pub fn to_node(&self, name: &str) -> Result<Node, String> {
+        let transport = if let Some(url) = &self.url {
+            let token = match &self.token_env {
+                Some(var) => std::env::var(var).ok().filter(|token| !token.is_empty()),
+                None => crate::utils::tokens::get_token(url),
+            };
+            Transport::remote(url, token)?
+        } else if let Some(socket) = &self.socket {
+            Transport::Unix(PathBuf::from(socket))
+        } else {
+            return Err(format!("Node {} has no socket or URL configured!", name));
+        };
+        Ok(Node {
+            name: Some(name.to_owned()),
+            client: OctyneClient::new(transport),
+            format: self.format.clone(),
+            pager: self.pager.clone(),
+        })
+    }
+
+    pub fn location(&self) -> String {
+        self.url
+            .clone()
+            .or_else(|| self.socket.clone())
+            .unwrap_or_default()
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("octynectl"))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.json"))
+}
+
+pub fn read_config() -> Result<Config, String> {
+    let path = match config_path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("Failed to read {}! {}", path.display(), e)),
+    };
+    serde_json::from_str(&contents).map_err(|e| format!("Invalid config {}! {}", path.display(), e))
+}
+
+pub fn write_config(config: &Config) -> Result<(), String> {
+    let path = config_path().ok_or("Unable to find config directory!")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}! {}", parent.display(), e))?;
+    }
+    let contents = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    fs::write(&path, contents + "\n")
+        .map_err(|e| format!("Failed to write {}! {}", path.display(), e))
+}
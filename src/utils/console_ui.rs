@@ -0,0 +1,347 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{stdout, Write},
+    process::exit,
+};
+
+use crossterm::{
+    cursor,
+    event::{KeyCode, KeyEvent, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal,
+};
+
+use crate::utils::line_editor::LineEditor;
+
+// Lines of output kept in the scrollback, older lines are discarded.
+const SCROLLBACK: usize = 10000;
+
+const PROMPT: &str = "> ";
+
+// Number of the most recent lines of output used to find where to resume from in the logs sent by
+// Octyne after reconnecting.
+const RESUME_LINES: usize = 20;
+
+pub enum Connection {
+    Connected,
+    Disconnected,
+    Reconnecting(u32),
+}
+
+impl Display for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Connection::Connected => write!(f, "Connected"),
+            Connection::Disconnected => write!(f, "Disconnected"),
+            Connection::Reconnecting(attempt) => write!(f, "Reconnecting… (attempt {})", attempt),
+        }
+    }
+}
+
+pub enum UiAction {
+    Send(String),
+    Quit,
+}
+
+// Full-screen console made up of the app's output, a status bar and an input line pinned to the
+// bottom, so that output arriving while typing doesn't mix with the input.
+pub struct ConsoleUi {
+    app: String,
+    pub v2: bool,
+    pub connection: Connection,
+    lines: VecDeque<String>,
+    // The last lines of output from the app, excluding notices from octynectl.
+    recent: VecDeque<String>,
+    // Number of rows the output is scrolled up by, 0 to follow new output.
+    scroll: usize,
+    editor: LineEditor,
+}
+
+impl ConsoleUi {
+    pub fn new(app: String, v2: bool, history: Vec<String>) -> ConsoleUi {
+        ConsoleUi {
+            app,
+            v2,
+            connection: Connection::Connected,
+            lines: VecDeque::new(),
+            recent: VecDeque::new(),
+            scroll: 0,
+            editor: LineEditor::new(history),
+        }
+    }
+
+    pub fn enter() {
+        terminal::enable_raw_mode().unwrap_or_else(|e| {
+            println!("Error: Failed to set up terminal! {}", e);
+            exit(1);
+        });
+        execute!(stdout(), terminal::EnterAlternateScreen).unwrap_or_default();
+    }
+
+    pub fn leave() {
+        execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen).unwrap_or_default();
+        terminal::disable_raw_mode().unwrap_or_default();
+    }
+
+    pub fn push_output(&mut self, data: &str) {
+        for line in split_lines(data) {
+            self.push_line(line, true);
+        }
+    }
+
+    // Octyne sends all the logs so far after reconnecting, so only the lines after the most recent
+    // output already shown are added (or all of them, if it can't be found e.g. after a restart).
+    pub fn push_logs(&mut self, data: &str) {
+        let lines = split_lines(data);
+        let recent = self.recent.iter().collect::<Vec<&String>>();
+        let start = match recent.len() {
+            0 => 0,
+            count => (count..=lines.len())
+                .rev()
+                .find(|end| lines[end - count..*end].iter().eq(recent.iter().copied()))
+                .unwrap_or(0),
+        };
+        for line in lines.into_iter().skip(start) {
+            self.push_line(line, true);
+        }
+    }
+
+    // Adds a message from octynectl e.g. about the connection to the output.
+    pub fn push_notice(&mut self, notice: &str) {
+        self.push_line(format!("[{}]", notice), false);
+    }
+
+    fn push_line(&mut self, line: String, output: bool) {
+        // Keep the view in place while scrolled up.
+        if self.scroll > 0 {
+            let width = terminal::size().map_or(80, |(width, _)| width as usize);
+            self.scroll += wrap(&line, width).len();
+        }
+        if output {
+            self.recent.push_back(line.clone());
+            if self.recent.len() > RESUME_LINES {
+                self.recent.pop_front();
+            }
+        }
+        self.lines.push_back(line);
+        if self.lines.len() > SCROLLBACK {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<UiAction> {
+        let page = terminal::size().map_or(22, |(_, height)| height.saturating_sub(2) as usize);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => Some(UiAction::Quit),
+            KeyCode::Char('d') if ctrl && self.editor.is_empty() => Some(UiAction::Quit),
+            KeyCode::PageUp => {
+                self.scroll += page.saturating_sub(1).max(1);
+                None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_sub(page.max(2) - 1);
+                None
+            }
+            _ => {
+                let line = self.editor.handle_key(key)?;
+                self.scroll = 0;
+                Some(UiAction::Send(line))
+            }
+        }
+    }
+
+    pub fn draw(&mut self) {
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        let (width, height) = (width as usize, height as usize);
+        let pane = height.saturating_sub(2);
+
+        // Wrap lines from the bottom up, until the pane and the rows scrolled past are filled.
+        let mut rows = Vec::new();
+        for line in self.lines.iter().rev() {
+            if rows.len() >= pane + self.scroll {
+                break;
+            }
+            rows.extend(wrap(line, width).into_iter().rev());
+        }
+        self.scroll = self.scroll.min(rows.len().saturating_sub(pane));
+        let visible = rows
+            .iter()
+            .skip(self.scroll)
+            .take(pane)
+            .rev()
+            .collect::<Vec<&String>>();
+
+        let mut stdout = stdout();
+        queue!(stdout, cursor::Hide, cursor::MoveTo(0, 0)).unwrap_or_default();
+        for index in 0..pane {
+            let row = visible.get(index).map_or("", |row| row.as_str());
+            queue!(
+                stdout,
+                Print(format!("{:width$}", row, width = width)),
+                cursor::MoveToNextLine(1),
+            )
+            .unwrap_or_default();
+        }
+
+        let protocol = if self.v2 { "console-v2" } else { "console v1" };
+        let mut status = format!(" {} | {} | {}", self.app, protocol, self.connection);
+        if self.scroll > 0 {
+            status += &format!(" | Scrolled up {} lines (PageDown to return)", self.scroll);
+        }
+        queue!(
+            stdout,
+            SetAttribute(Attribute::Reverse),
+            Print(fit(&status, width)),
+            SetAttribute(Attribute::Reset),
+            cursor::MoveToNextLine(1),
+        )
+        .unwrap_or_default();
+
+        // Scroll the input horizontally to keep the cursor visible.
+        let prompt = self.editor.prompt(PROMPT);
+        let prompt_width = prompt.chars().count();
+        let space = width.saturating_sub(prompt_width + 1).max(1);
+        let cursor = self.editor.cursor();
+        let offset = cursor.saturating_sub(space);
+        let input = self.editor.line()[offset..]
+            .iter()
+            .take(space)
+            .collect::<String>();
+        queue!(
+            stdout,
+            Print(fit(&format!("{}{}", prompt, input), width)),
+            cursor::MoveTo(
+                (prompt_width + cursor - offset).min(width.saturating_sub(1)) as u16,
+                height.saturating_sub(1) as u16
+            ),
+            cursor::Show,
+        )
+        .unwrap_or_default();
+        stdout.flush().unwrap_or_default();
+    }
+}
+
+fn split_lines(data: &str) -> Vec<String> {
+    strip_ansi(data)
+        .trim_end_matches(['\r', '\n'])
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r').to_owned())
+        .collect()
+}
+
+// Pads or truncates the text to exactly the given width.
+fn fit(text: &str, width: usize) -> String {
+    let text = text.chars().take(width).collect::<String>();
+    format!("{:width$}", text, width = width)
+}
+
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let chars = line.chars().collect::<Vec<char>>();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+// Removes ANSI escape sequences (e.g. colours) and other control characters, which would break the
+// layout of the screen, and expands tabs.
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI sequences end with a character in the range @ to ~.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC sequences end with BEL or ESC \.
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\t' => stripped.push_str("    "),
+            '\n' => stripped.push(c),
+            c if c.is_control() => {}
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(ui: &ConsoleUi) -> Vec<&str> {
+        ui.lines.iter().map(|line| line.as_str()).collect()
+    }
+
+    #[test]
+    fn resumes_after_the_last_output_shown() {
+        let mut ui = ConsoleUi::new("lobby".to_owned(), true, Vec::new());
+        ui.push_output("a\nb\n");
+        ui.push_output("c\n");
+        ui.push_notice("Reconnected.");
+        ui.push_logs("a\nb\nc\nd\ne\n");
+        assert_eq!(lines(&ui), ["a", "b", "c", "[Reconnected.]", "d", "e"]);
+    }
+
+    #[test]
+    fn resumes_after_the_last_occurrence_of_repeated_output() {
+        let mut ui = ConsoleUi::new("lobby".to_owned(), true, Vec::new());
+        ui.push_output("x\ny\n");
+        ui.push_logs("x\ny\nz\nx\ny\nw\n");
+        assert_eq!(lines(&ui), ["x", "y", "w"]);
+    }
+
+    #[test]
+    fn shows_all_logs_if_the_output_shown_is_missing() {
+        let mut ui = ConsoleUi::new("lobby".to_owned(), true, Vec::new());
+        ui.push_output("old\n");
+        ui.push_logs("new 1\nnew 2\n");
+        assert_eq!(lines(&ui), ["old", "new 1", "new 2"]);
+
+        let mut ui = ConsoleUi::new("lobby".to_owned(), true, Vec::new());
+        ui.push_logs("first\n");
+        assert_eq!(lines(&ui), ["first"]);
+    }
+
+    #[test]
+    fn only_compares_recent_output() {
+        let mut ui = ConsoleUi::new("lobby".to_owned(), true, Vec::new());
+        let output = (0..RESUME_LINES + 5)
+            .map(|i| format!("{}\n", i))
+            .collect::<String>();
+        ui.push_output(&output);
+        let logs = (5..RESUME_LINES + 7)
+            .map(|i| format!("{}\n", i))
+            .collect::<String>();
+        ui.push_logs(&logs);
+        assert_eq!(ui.lines.len(), RESUME_LINES + 7);
+        assert_eq!(ui.lines.back().map(|line| line.as_str()), Some("26"));
+    }
+
+    #[test]
+    fn strips_escape_sequences() {
+        assert_eq!(strip_ansi("\x1b[31mred\x1b[0m\tx\x07"), "red    x");
+        assert_eq!(strip_ansi("\x1b]0;title\x07text"), "text");
+        assert_eq!(split_lines("a\r\nb\r\n"), ["a", "b"]);
+        assert_eq!(wrap("abcde", 2), ["ab", "cd", "e"]);
+    }
+}
@@ -0,0 +1,86 @@
+use std::{fs, path::PathBuf};
+
+use crate::utils::config::Node;
+use octynectl::api::transport::Transport;
+
+// Number of lines of history kept for each app.
+const MAX_HISTORY: usize = 1000;
+
+// Console input history is stored per node and app, one line per entry. Nodes without a name
+// (e.g. with --socket) are told apart by their location, prefixed with @ so they can't clash with
+// named nodes.
+pub fn history_path(node: &Node, app: &str) -> Option<PathBuf> {
+    let node = match &node.name {
+        Some(name) => escape(name),
+        None => match node.client.transport() {
+            Transport::Unix(path) => format!("@{}", escape(&path.to_string_lossy())),
+            Transport::Remote { url, .. } => format!("@{}", escape(url)),
+        },
+    };
+    let dir = dirs::data_dir()?.join("octynectl").join("history");
+    Some(dir.join(node).join(escape(app)))
+}
+
+// Turns a name into a file name, replacing characters other than letters, digits, - _ and . (and
+// prefixing names like . and .. which refer to directories).
+fn escape(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || "-_.".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+    match name.chars().all(|c| c == '.') {
+        true => format!("_{}", name),
+        false => name,
+    }
+}
+
+pub fn read_history(node: &Node, app: &str) -> Vec<String> {
+    history_path(node, app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|contents| contents.lines().map(|line| line.to_owned()).collect())
+        .unwrap_or_default()
+}
+
+pub fn add_history(node: &Node, app: &str, line: &str) -> Result<(), String> {
+    let path = history_path(node, app).ok_or("Unable to find data directory!")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}! {}", parent.display(), e))?;
+    }
+    let mut history = read_history(node, app);
+    if history.last().map(|last| last.as_str()) == Some(line) {
+        return Ok(());
+    }
+    history.push(line.to_owned());
+    let skip = history.len().saturating_sub(MAX_HISTORY);
+    let contents = history[skip..].join("\n") + "\n";
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}! {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_names() {
+        assert_eq!(escape("survival-1.20_b"), "survival-1.20_b");
+        assert_eq!(escape("a/b c"), "a_b_c");
+        assert_eq!(escape("."), "_.");
+        assert_eq!(escape(".."), "_..");
+        assert_eq!(escape(""), "_");
+        assert_eq!(escape("..a"), "..a");
+    }
+
+    #[test]
+    fn keeps_history_per_node() {
+        let mut node = Node::new(Transport::Unix(PathBuf::from("/run/octyne.sock")));
+        let unnamed = history_path(&node, "lobby").unwrap();
+        assert!(unnamed.ends_with("history/@_run_octyne.sock/lobby"));
+        node.name = Some("..".to_owned());
+        let named = history_path(&node, "..").unwrap();
+        assert!(named.ends_with("history/_../_.."));
+    }
+}
@@ -0,0 +1,386 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// State of a reverse history search (Ctrl-R), with the line being edited before the search began.
+struct Search {
+    query: String,
+    found: Option<usize>,
+    // Whether nothing matches the query, in which case the last match is still shown.
+    failing: bool,
+    original: Vec<char>,
+}
+
+// Readline-style line editor with history, supporting the usual Emacs keybindings e.g. Ctrl-A/E,
+// Alt-B/F for word movement, Ctrl-W/K/U/Y to kill and yank text, and Ctrl-R to search history.
+pub struct LineEditor {
+    line: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    // Index of the history entry being shown, None when editing a new line.
+    history_index: Option<usize>,
+    // The new line being edited, while going through history.
+    draft: Vec<char>,
+    search: Option<Search>,
+    killed: Vec<char>,
+}
+
+impl LineEditor {
+    pub fn new(history: Vec<String>) -> LineEditor {
+        LineEditor {
+            line: Vec::new(),
+            cursor: 0,
+            history,
+            history_index: None,
+            draft: Vec::new(),
+            search: None,
+            killed: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.line.is_empty()
+    }
+
+    // The prompt to show before the line, which changes while searching history.
+    pub fn prompt(&self, default: &str) -> String {
+        match &self.search {
+            Some(search) if search.failing => {
+                format!("(failing reverse-i-search)`{}': ", search.query)
+            }
+            Some(search) => format!("(reverse-i-search)`{}': ", search.query),
+            None => default.to_owned(),
+        }
+    }
+
+    pub fn line(&self) -> &[char] {
+        &self.line
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // Handles a key press, returning the line once it is submitted with Enter.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<String> {
+        if self.search.is_some() && self.handle_search_key(key) {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.line.len(),
+            KeyCode::Char('b') if ctrl => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Char('f') if ctrl => self.cursor = (self.cursor + 1).min(self.line.len()),
+            KeyCode::Char('b') if alt => self.cursor = self.word_start(),
+            KeyCode::Char('f') if alt => self.cursor = self.word_end(),
+            KeyCode::Char('d') if alt => self.kill(self.cursor, self.word_end()),
+            KeyCode::Char('h') if ctrl => self.backspace(),
+            KeyCode::Char('w') if ctrl => self.kill(self.word_start(), self.cursor),
+            KeyCode::Char('u') if ctrl => self.kill(0, self.cursor),
+            KeyCode::Char('k') if ctrl => self.kill(self.cursor, self.line.len()),
+            KeyCode::Char('y') if ctrl => {
+                let killed = self.killed.clone();
+                self.insert(&killed);
+            }
+            KeyCode::Char('p') if ctrl => self.history_previous(),
+            KeyCode::Char('n') if ctrl => self.history_next(),
+            KeyCode::Char('r') if ctrl => {
+                self.search = Some(Search {
+                    query: String::new(),
+                    found: None,
+                    failing: false,
+                    original: self.line.clone(),
+                });
+            }
+            KeyCode::Char(c) if !ctrl && !alt => self.insert(&[c]),
+            KeyCode::Backspace if alt || ctrl => self.kill(self.word_start(), self.cursor),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            KeyCode::Left if alt || ctrl => self.cursor = self.word_start(),
+            KeyCode::Right if alt || ctrl => self.cursor = self.word_end(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.line.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.line.len(),
+            KeyCode::Up => self.history_previous(),
+            KeyCode::Down => self.history_next(),
+            _ => {}
+        }
+        None
+    }
+
+    // Handles keys specific to searching, returning false if the search was ended by the key and
+    // the key should be handled as usual (e.g. to move the cursor in the found line).
+    fn handle_search_key(&mut self, key: KeyEvent) -> bool {
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('r') if ctrl => {
+                let before = search.found.unwrap_or(self.history.len());
+                self.find(before);
+            }
+            KeyCode::Char('g') if ctrl => self.cancel_search(),
+            KeyCode::Esc => self.cancel_search(),
+            KeyCode::Char(c) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {
+                search.query.push(c);
+                let before = search.found.map_or(self.history.len(), |found| found + 1);
+                self.find(before);
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                self.find(self.history.len());
+            }
+            _ => {
+                self.search = None;
+                self.history_index = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    // Finds the most recent history entry before the given index containing the search query.
+    fn find(&mut self, before: usize) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.failing = false;
+        if search.query.is_empty() {
+            search.found = None;
+            return;
+        }
+        let found = self.history[..before]
+            .iter()
+            .rposition(|entry| entry.contains(&search.query));
+        if let Some(index) = found {
+            let entry = &self.history[index];
+            let position = entry.find(&search.query).unwrap_or(0);
+            self.line = entry.chars().collect();
+            self.cursor = entry[..position].chars().count();
+            search.found = Some(index);
+        } else {
+            search.failing = true;
+            if search.found.is_none() {
+                self.line = search.original.clone();
+                self.cursor = self.line.len();
+            }
+        }
+    }
+
+    fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.line = search.original;
+            self.cursor = self.line.len();
+        }
+    }
+
+    fn submit(&mut self) -> Option<String> {
+        self.search = None;
+        self.history_index = None;
+        if self.line.is_empty() {
+            return None;
+        }
+        let line = self.line.drain(..).collect::<String>();
+        self.cursor = 0;
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        Some(line)
+    }
+
+    fn insert(&mut self, chars: &[char]) {
+        self.line
+            .splice(self.cursor..self.cursor, chars.iter().cloned());
+        self.cursor += chars.len();
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.line.remove(self.cursor);
+        }
+    }
+
+    fn kill(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.killed = self.line.drain(start..end).collect();
+            self.cursor = start;
+        }
+    }
+
+    fn word_start(&self) -> usize {
+        let mut index = self.cursor;
+        while index > 0 && !self.line[index - 1].is_alphanumeric() {
+            index -= 1;
+        }
+        while index > 0 && self.line[index - 1].is_alphanumeric() {
+            index -= 1;
+        }
+        index
+    }
+
+    fn word_end(&self) -> usize {
+        let mut index = self.cursor;
+        while index < self.line.len() && !self.line[index].is_alphanumeric() {
+            index += 1;
+        }
+        while index < self.line.len() && self.line[index].is_alphanumeric() {
+            index += 1;
+        }
+        index
+    }
+
+    fn history_previous(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.line.clone();
+                self.history.len() - 1
+            }
+        };
+        self.history_index = Some(index);
+        self.line = self.history[index].chars().collect();
+        self.cursor = self.line.len();
+    }
+
+    fn history_next(&mut self) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.history_index = Some(index + 1);
+            self.line = self.history[index + 1].chars().collect();
+        } else {
+            self.history_index = None;
+            self.line = std::mem::take(&mut self.draft);
+        }
+        self.cursor = self.line.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn alt(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+
+    fn type_text(editor: &mut LineEditor, text: &str) {
+        for c in text.chars() {
+            editor.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn line(editor: &LineEditor) -> String {
+        editor.line().iter().collect()
+    }
+
+    #[test]
+    fn submits_lines_into_history() {
+        let mut editor = LineEditor::new(Vec::new());
+        type_text(&mut editor, "say hi");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            Some("say hi".to_owned())
+        );
+        assert!(editor.is_empty());
+        assert_eq!(editor.handle_key(key(KeyCode::Enter)), None);
+        editor.handle_key(key(KeyCode::Up));
+        assert_eq!(line(&editor), "say hi");
+    }
+
+    #[test]
+    fn moves_by_words() {
+        let mut editor = LineEditor::new(Vec::new());
+        type_text(&mut editor, "say hello world");
+        editor.handle_key(alt('b'));
+        assert_eq!(editor.cursor(), 10);
+        editor.handle_key(alt('b'));
+        assert_eq!(editor.cursor(), 4);
+        editor.handle_key(alt('f'));
+        assert_eq!(editor.cursor(), 9);
+        editor.handle_key(ctrl('a'));
+        assert_eq!(editor.cursor(), 0);
+        editor.handle_key(ctrl('e'));
+        assert_eq!(editor.cursor(), 15);
+    }
+
+    #[test]
+    fn kills_and_yanks_text() {
+        let mut editor = LineEditor::new(Vec::new());
+        type_text(&mut editor, "say hello world");
+        editor.handle_key(ctrl('w'));
+        assert_eq!(line(&editor), "say hello ");
+        editor.handle_key(ctrl('a'));
+        editor.handle_key(ctrl('y'));
+        assert_eq!(line(&editor), "worldsay hello ");
+        editor.handle_key(ctrl('k'));
+        assert_eq!(line(&editor), "world");
+        editor.handle_key(ctrl('e'));
+        editor.handle_key(ctrl('y'));
+        assert_eq!(line(&editor), "worldsay hello ");
+        editor.handle_key(ctrl('u'));
+        assert!(editor.is_empty());
+    }
+
+    #[test]
+    fn navigates_history_keeping_the_draft() {
+        let mut editor = LineEditor::new(vec!["first".to_owned(), "second".to_owned()]);
+        type_text(&mut editor, "draft");
+        editor.handle_key(key(KeyCode::Up));
+        assert_eq!(line(&editor), "second");
+        editor.handle_key(ctrl('p'));
+        assert_eq!(line(&editor), "first");
+        editor.handle_key(key(KeyCode::Up));
+        assert_eq!(line(&editor), "first");
+        editor.handle_key(key(KeyCode::Down));
+        editor.handle_key(ctrl('n'));
+        assert_eq!(line(&editor), "draft");
+    }
+
+    #[test]
+    fn searches_history() {
+        let history = ["say one", "list", "say two"].map(|entry| entry.to_owned());
+        let mut editor = LineEditor::new(history.to_vec());
+        editor.handle_key(ctrl('r'));
+        assert_eq!(editor.prompt("> "), "(reverse-i-search)`': ");
+        type_text(&mut editor, "say");
+        assert_eq!(line(&editor), "say two");
+        editor.handle_key(ctrl('r'));
+        assert_eq!(line(&editor), "say one");
+        type_text(&mut editor, "x");
+        assert_eq!(editor.prompt("> "), "(failing reverse-i-search)`sayx': ");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            Some("say one".to_owned())
+        );
+        assert_eq!(editor.prompt("> "), "> ");
+    }
+
+    #[test]
+    fn cancels_search() {
+        let mut editor = LineEditor::new(vec!["say one".to_owned()]);
+        type_text(&mut editor, "li");
+        editor.handle_key(ctrl('r'));
+        type_text(&mut editor, "say");
+        assert_eq!(line(&editor), "say one");
+        editor.handle_key(ctrl('g'));
+        assert_eq!(line(&editor), "li");
+        assert_eq!(editor.prompt("> "), "> ");
+    }
+}
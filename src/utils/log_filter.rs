@@ -0,0 +1,364 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use regex::Regex;
+
+use crate::utils::misc::parse_duration;
+
+// Formats of timestamps at the start of log lines (optionally in brackets), e.g. those used by
+// log4j, Minecraft servers and most logging libraries. Time zones are ignored.
+const DATE_TIME_FORMATS: [&str; 3] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y/%m/%d %H:%M:%S",
+];
+
+// Filters log lines by time and regex, which works on the logs so far as well as on output as it
+// arrives (lines are passed through one by one, like grep).
+pub struct LogFilter {
+    tail: Option<usize>,
+    since: Option<NaiveDateTime>,
+    grep: Option<Regex>,
+    invert: bool,
+    context: usize,
+    // Whether a line with a timestamp after --since has been reached.
+    reached: bool,
+    // Lines before the next match, kept for --context.
+    before: VecDeque<String>,
+    // Number of lines after the last match still to be shown for --context.
+    after: usize,
+    // Whether lines were left out since the last line shown, to separate matches with --.
+    gap: bool,
+    shown: bool,
+    // Output received after the last complete line, when filtering output as it arrives.
+    pending: String,
+}
+
+impl LogFilter {
+    pub fn from_opts(opts: &HashMap<String, String>) -> Result<LogFilter, String> {
+        let grep = match opts.get("grep") {
+            Some(grep) => Some(Regex::new(grep).map_err(|e| format!("Invalid regex! {}", e))?),
+            None => None,
+        };
+        let since = match opts.get("since") {
+            Some(since) => Some(parse_since(since)?),
+            None => None,
+        };
+        Ok(LogFilter {
+            tail: opts.get("tail").and_then(|tail| tail.parse().ok()),
+            since,
+            grep,
+            invert: opts.contains_key("invert"),
+            context: opts
+                .get("context")
+                .and_then(|context| context.parse().ok())
+                .unwrap_or(0),
+            reached: false,
+            before: VecDeque::new(),
+            after: 0,
+            gap: false,
+            shown: false,
+            pending: String::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tail.is_none() && self.since.is_none() && self.grep.is_none()
+    }
+
+    // Filters the logs so far, applying --tail to the result.
+    pub fn filter_logs(&mut self, logs: &str) -> String {
+        if self.is_empty() {
+            return logs.to_owned();
+        }
+        let lines = logs
+            .lines()
+            .flat_map(|line| self.filter_line(line))
+            .collect::<Vec<String>>();
+        let mut skip = self.tail.map_or(0, |tail| lines.len().saturating_sub(tail));
+        // Don't start with the separator between groups of lines when some were left out.
+        if skip > 0 && lines.get(skip).is_some_and(|line| line == "--") {
+            skip += 1;
+        }
+        lines[skip..].join("\n")
+    }
+
+    // Filters output as it arrives, which may end in the middle of a line. The rest of the line is
+    // kept until the next output completes it (or until flush is called).
+    pub fn filter_chunk(&mut self, data: &str) -> Vec<String> {
+        self.pending.push_str(data);
+        let Some(end) = self.pending.rfind('\n') else {
+            return Vec::new();
+        };
+        let complete = self.pending.drain(..=end).collect::<String>();
+        complete[..end]
+            .split('\n')
+            .flat_map(|line| self.filter_line(line.strip_suffix('\r').unwrap_or(line)))
+            .collect()
+    }
+
+    // Filters the incomplete line kept by filter_chunk, if any, e.g. once the output has ended.
+    pub fn flush(&mut self) -> Vec<String> {
+        match std::mem::take(&mut self.pending) {
+            line if line.is_empty() => Vec::new(),
+            line => self.filter_line(&line),
+        }
+    }
+
+    // Returns the lines to show for this line of the logs, which include preceding lines (and a --
+    // separator) if it matches --grep with --context.
+    pub fn filter_line(&mut self, line: &str) -> Vec<String> {
+        if let (Some(since), false) = (self.since, self.reached) {
+            match parse_timestamp(line) {
+                Some(timestamp) if timestamp >= since => self.reached = true,
+                _ => return Vec::new(),
+            }
+        }
+        let matches = self
+            .grep
+            .as_ref()
+            .is_none_or(|grep| grep.is_match(line) != self.invert);
+        let mut lines = Vec::new();
+        if matches {
+            if self.gap && self.shown && self.context > 0 {
+                lines.push("--".to_owned());
+            }
+            lines.extend(self.before.drain(..));
+            lines.push(line.to_owned());
+            self.after = self.context;
+            self.gap = false;
+            self.shown = true;
+        } else if self.after > 0 {
+            self.after -= 1;
+            lines.push(line.to_owned());
+        } else {
+            self.before.push_back(line.to_owned());
+            if self.before.len() > self.context {
+                self.before.pop_front();
+                self.gap = true;
+            }
+        }
+        lines
+    }
+}
+
+fn parse_since(since: &str) -> Result<NaiveDateTime, String> {
+    let now = Local::now().naive_local();
+    if let Ok(duration) = parse_duration(since) {
+        return TimeDelta::from_std(duration)
+            .ok()
+            .and_then(|duration| now.checked_sub_signed(duration))
+            .ok_or_else(|| format!("Invalid --since duration \"{}\"!", since));
+    }
+    let date_time_formats = DATE_TIME_FORMATS
+        .iter()
+        .flat_map(|format| [format.to_string(), format.replace(":%S", "")]);
+    for format in date_time_formats {
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(since, &format) {
+            return Ok(timestamp);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN));
+    }
+    for format in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(since, format) {
+            return Ok(today_at(time, now));
+        }
+    }
+    Err(format!(
+        "Invalid --since value \"{}\"! (e.g. 10m, 10:30, 2024-01-15 10:30)",
+        since
+    ))
+}
+
+// Parses the timestamp at the start of a log line, if any. Times without a date are assumed to be
+// from the last 24 hours.
+fn parse_timestamp(line: &str) -> Option<NaiveDateTime> {
+    let line = line.trim_start_matches(['[', ' ']);
+    let prefix = |length: usize| line.get(..length);
+    for format in DATE_TIME_FORMATS {
+        if let Some(Ok(timestamp)) =
+            prefix(19).map(|prefix| NaiveDateTime::parse_from_str(prefix, format))
+        {
+            return Some(timestamp);
+        }
+    }
+    let time = NaiveTime::parse_from_str(prefix(8)?, "%H:%M:%S").ok()?;
+    Some(today_at(time, Local::now().naive_local()))
+}
+
+fn today_at(time: NaiveTime, now: NaiveDateTime) -> NaiveDateTime {
+    let timestamp = now.date().and_time(time);
+    match timestamp > now + TimeDelta::minutes(1) {
+        true => timestamp - TimeDelta::days(1),
+        false => timestamp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGS: &str = "2024-01-15 10:00:00,000 [main] INFO Starting
+2024-01-15 10:00:01,000 [main] INFO Loading x
+  at line without a timestamp
+2024-01-15 10:00:02,000 [main] WARN Slow x
+2024-01-15 10:00:03,000 [main] INFO Done
+2024-01-15 10:00:04,000 [main] INFO Ready x";
+
+    fn filter(opts: &[(&str, &str)]) -> LogFilter {
+        let opts = opts
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        LogFilter::from_opts(&opts).unwrap()
+    }
+
+    fn filter_logs(opts: &[(&str, &str)]) -> Vec<String> {
+        let logs = filter(opts).filter_logs(LOGS);
+        logs.lines()
+            // Leave out timestamps to keep the expected lines short.
+            .map(|line| match line.starts_with("2024") {
+                true => line[24..].to_owned(),
+                false => line.trim().to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leaves_logs_unchanged_without_filters() {
+        assert_eq!(filter(&[]).filter_logs(LOGS), LOGS);
+    }
+
+    #[test]
+    fn filters_by_regex() {
+        assert_eq!(
+            filter_logs(&[("grep", "x$")]),
+            [
+                "[main] INFO Loading x",
+                "[main] WARN Slow x",
+                "[main] INFO Ready x"
+            ]
+        );
+        assert_eq!(
+            filter_logs(&[("grep", "INFO"), ("invert", "")]),
+            ["at line without a timestamp", "[main] WARN Slow x"]
+        );
+    }
+
+    #[test]
+    fn shows_context_around_matches() {
+        assert_eq!(
+            filter_logs(&[("grep", "Starting|Done"), ("context", "1")]),
+            [
+                "[main] INFO Starting",
+                "[main] INFO Loading x",
+                "--",
+                "[main] WARN Slow x",
+                "[main] INFO Done",
+                "[main] INFO Ready x",
+            ]
+        );
+        assert_eq!(
+            filter_logs(&[("grep", "Loading|Slow"), ("context", "1")]),
+            [
+                "[main] INFO Starting",
+                "[main] INFO Loading x",
+                "at line without a timestamp",
+                "[main] WARN Slow x",
+                "[main] INFO Done",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_last_lines() {
+        assert_eq!(
+            filter_logs(&[("tail", "2")]),
+            ["[main] INFO Done", "[main] INFO Ready x"]
+        );
+        assert_eq!(filter_logs(&[("tail", "0")]), Vec::<String>::new());
+        assert_eq!(filter_logs(&[("tail", "100")]).len(), 6);
+    }
+
+    #[test]
+    fn does_not_start_with_a_separator_after_tail() {
+        assert_eq!(
+            filter_logs(&[("grep", "Starting|Done"), ("context", "1"), ("tail", "4")]),
+            [
+                "[main] WARN Slow x",
+                "[main] INFO Done",
+                "[main] INFO Ready x"
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_lines_split_across_chunks() {
+        let mut filter = filter(&[("grep", "Done")]);
+        assert!(filter
+            .filter_chunk("[main] INFO Starting\n[main] INFO Do")
+            .is_empty());
+        assert_eq!(
+            filter.filter_chunk("ne\r\n[main] INFO "),
+            ["[main] INFO Done"]
+        );
+        assert!(filter.filter_chunk("Ready\n").is_empty());
+        assert!(filter.filter_chunk("Done without a newline").is_empty());
+        assert_eq!(filter.flush(), ["Done without a newline"]);
+        assert!(filter.flush().is_empty());
+    }
+
+    #[test]
+    fn filters_by_time() {
+        assert_eq!(
+            filter_logs(&[("since", "2024-01-15 10:00:02")]),
+            [
+                "[main] WARN Slow x",
+                "[main] INFO Done",
+                "[main] INFO Ready x"
+            ]
+        );
+        assert_eq!(
+            filter_logs(&[("since", "2024-01-15T10:00:01"), ("grep", "line ")]),
+            ["at line without a timestamp"]
+        );
+        assert_eq!(
+            filter_logs(&[("since", "2024-01-16")]),
+            Vec::<String>::new()
+        );
+        assert_eq!(filter_logs(&[("since", "2024-01-15")]).len(), 6);
+    }
+
+    #[test]
+    fn parses_since() {
+        assert!(parse_since("10m").unwrap() < Local::now().naive_local());
+        assert!(parse_since("10:30").is_ok());
+        assert!(parse_since("2024-01-15 10:30").is_ok());
+        assert!(parse_since("yesterday").is_err());
+        assert!(LogFilter::from_opts(&HashMap::from([("grep".into(), "(".into())])).is_err());
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let timestamp = date.and_hms_opt(10, 30, 0);
+        assert_eq!(parse_timestamp("2024-01-15T10:30:00Z msg"), timestamp);
+        assert_eq!(parse_timestamp("[2024-01-15 10:30:00] msg"), timestamp);
+        assert_eq!(parse_timestamp("2024/01/15 10:30:00 msg"), timestamp);
+        assert!(parse_timestamp("[10:30:00] [Server thread/INFO]: msg").is_some());
+        assert_eq!(parse_timestamp("no timestamp"), None);
+        assert_eq!(parse_timestamp("[10:30"), None);
+    }
+
+    #[test]
+    fn assumes_times_are_from_the_last_day() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let now = date.and_hms_opt(0, 5, 0).unwrap();
+        let time = NaiveTime::from_hms_opt(23, 59, 0).unwrap();
+        assert_eq!(today_at(time, now), date.pred_opt().unwrap().and_time(time));
+        let time = NaiveTime::from_hms_opt(0, 1, 0).unwrap();
+        assert_eq!(today_at(time, now), date.and_time(time));
+    }
+}
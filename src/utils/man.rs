@@ -0,0 +1,131 @@
+use crate::utils::cli::{self, Command};
+
+// e.g. octynectl-config-edit, man pages for subcommands are named like git's.
+pub fn page_name(path: &[&'static Command]) -> String {
+    path.iter()
+        .map(|cmd| cmd.name)
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+// Paths to every command with a man page, i.e. the root command and all non-hidden subcommands.
+pub fn page_paths(path: Vec<&'static Command>) -> Vec<Vec<&'static Command>> {
+    let command = path[path.len() - 1];
+    let mut paths = vec![path.clone()];
+    for subcommand in command.subcommands.iter().filter(|cmd| !cmd.hidden) {
+        let mut subpath = path.clone();
+        subpath.push(subcommand);
+        paths.extend(page_paths(subpath));
+    }
+    paths
+}
+
+pub fn man_page(path: &[&'static Command]) -> String {
+    let command = path[path.len() - 1];
+    let name = page_name(path);
+    let mut page = format!(
+        ".TH \"{}\" \"1\" \"\" \"octynectl {}\" \"User Commands\"\n",
+        name.to_uppercase(),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    page.push_str(".SH NAME\n");
+    page.push_str(&format!(
+        "{} \\- {}\n",
+        escape(&name),
+        escape(command.summary)
+    ));
+
+    page.push_str(".SH SYNOPSIS\n");
+    let usage = cli::usage(path);
+    let (program, rest) = usage.split_once(" [").unwrap_or((&usage, ""));
+    page.push_str(&format!("\\fB{}\\fR", escape(program)));
+    if !rest.is_empty() {
+        page.push_str(&format!(" [{}", escape(rest)));
+    }
+    page.push('\n');
+
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(&format!("{}.\n", escape(command.summary)));
+    write_paragraphs(&mut page, command.description);
+    if path.len() > 1 && !command.aliases.is_empty() {
+        page.push_str(".PP\n");
+        page.push_str(&format!(
+            "Aliases: {}\n",
+            escape(&command.aliases.join(", "))
+        ));
+    }
+
+    let subcommands = command.subcommands.iter().filter(|cmd| !cmd.hidden);
+    if subcommands.clone().next().is_some() {
+        page.push_str(".SH SUBCOMMANDS\n");
+        for subcommand in subcommands.clone() {
+            let names = std::iter::once(&subcommand.name)
+                .chain(subcommand.aliases.iter())
+                .map(|name| format!("\\fB{}\\fR", escape(name)))
+                .collect::<Vec<String>>();
+            page.push_str(&format!(".TP\n{}\n", names.join(", ")));
+            page.push_str(&format!("{}.\n", escape(subcommand.summary)));
+        }
+    }
+
+    let options = cli::options(path);
+    if !options.is_empty() {
+        page.push_str(".SH OPTIONS\n");
+        for opt in options {
+            page.push_str(&format!(".TP\n\\fB{}\\fR\n", escape(&opt.usage())));
+            for line in cli::option_help(opt).lines() {
+                page.push_str(&format!("{}\n", escape_line(line)));
+            }
+        }
+    }
+
+    // Link to the parent command (for the global options) and to any subcommands.
+    let mut see_also = Vec::new();
+    if path.len() > 1 {
+        see_also.push(page_name(&path[..path.len() - 1]));
+    }
+    for subcommand in subcommands {
+        see_also.push(format!("{}-{}", name, subcommand.name));
+    }
+    if !see_also.is_empty() {
+        page.push_str(".SH SEE ALSO\n");
+        let see_also = see_also
+            .iter()
+            .map(|page| format!("\\fB{}\\fR(1)", escape(page)))
+            .collect::<Vec<String>>();
+        page.push_str(&format!("{}\n", see_also.join(", ")));
+    }
+    page
+}
+
+// Indented paragraphs (e.g. examples) are kept as-is, the rest are filled by the formatter.
+fn write_paragraphs(page: &mut String, text: &str) {
+    for paragraph in text.trim().split("\n\n").filter(|p| !p.trim().is_empty()) {
+        page.push_str(".PP\n");
+        let preformatted = paragraph.lines().any(|line| line.starts_with(' '));
+        if preformatted {
+            page.push_str(".nf\n");
+        }
+        for line in paragraph.lines() {
+            page.push_str(&format!("{}\n", escape_line(line)));
+        }
+        if preformatted {
+            page.push_str(".fi\n");
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+// Lines starting with . or ' would be interpreted as requests by roff.
+fn escape_line(line: &str) -> String {
+    let line = escape(line);
+    if line.starts_with('.') || line.starts_with('\'') {
+        format!("\\&{}", line)
+    } else {
+        line
+    }
+}
@@ -1,8 +1,238 @@
 use std::{
+    collections::HashMap,
     env,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
+use tokio::time::Instant;
+
+use crate::utils::cli::Opt;
+use crate::utils::config::{read_config, Node};
+use octynectl::api::transport::Transport;
+
+pub const DEFAULT_PORT: u16 = 42069;
+
+pub const DEFAULT_PARALLELISM: usize = 10;
+
+// Roughly 30 years, used in place of instants too far in the future to represent.
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+pub const PARALLEL_OPTION: Opt = Opt::value(
+    "parallel",
+    "count",
+    "Act on up to this many apps at once (default 10),
+and print a summary of the results at the end",
+)
+.positive()
+.optional()
+.short('p');
+
 pub fn default_octyne_path() -> PathBuf {
-    Path::new(&env::temp_dir()).join("octyne.sock.42069")
+    octyne_path_for_port(DEFAULT_PORT)
+}
+
+pub fn octyne_path_for_port(port: u16) -> PathBuf {
+    Path::new(&env::temp_dir()).join(format!("octyne.sock.{}", port))
+}
+
+// Priority: --socket, then --port, then $OCTYNE_SOCKET, then the default path.
+pub fn octyne_path(top_level_opts: &HashMap<String, String>) -> Result<PathBuf, String> {
+    if let Some(socket) = top_level_opts.get("socket") {
+        if socket.is_empty() {
+            return Err("No value provided for flag --socket!".to_string());
+        }
+        return Ok(PathBuf::from(socket));
+    } else if let Some(port) = top_level_opts.get("port") {
+        return match port.parse::<u16>() {
+            Ok(port) if port != 0 => Ok(octyne_path_for_port(port)),
+            _ => Err(format!("Invalid value for flag --port \"{}\"!", port)),
+        };
+    }
+    match env::var("OCTYNE_SOCKET") {
+        Ok(socket) if !socket.is_empty() => Ok(PathBuf::from(socket)),
+        _ => Ok(default_octyne_path()),
+    }
+}
+
+// Priority: --node, then --url/--socket/--port, then $OCTYNE_NODE, then $OCTYNE_URL and
+// $OCTYNE_SOCKET, then the default node in the config file, then the default Unix socket.
+pub fn octyne_node(top_level_opts: &HashMap<String, String>) -> Result<Node, String> {
+    let node_name = match top_level_opts.get("node") {
+        Some(name) if name.is_empty() => {
+            return Err("No value provided for flag --node!".to_string())
+        }
+        Some(name) => Some(name.to_owned()),
+        None if ["url", "socket", "port"]
+            .iter()
+            .any(|key| top_level_opts.contains_key(*key)) =>
+        {
+            return octyne_transport(top_level_opts).map(Node::new)
+        }
+        None => env::var("OCTYNE_NODE").ok().filter(|name| !name.is_empty()),
+    };
+    if node_name.is_none()
+        && (env::var("OCTYNE_URL").is_ok_and(|url| !url.is_empty())
+            || env::var("OCTYNE_SOCKET").is_ok_and(|socket| !socket.is_empty()))
+    {
+        return octyne_transport(top_level_opts).map(Node::new);
+    }
+
+    let config = read_config()?;
+    match node_name.or(config.default_node) {
+        Some(name) => match config.nodes.get(&name) {
+            Some(node) => node.to_node(&name),
+            None => Err(format!("No node named {} exists!", name)),
+        },
+        None => octyne_transport(top_level_opts).map(Node::new),
+    }
+}
+
+// Priority: --url, then --socket/--port, then $OCTYNE_URL, then the Unix socket.
+pub fn octyne_transport(top_level_opts: &HashMap<String, String>) -> Result<Transport, String> {
+    let url = match top_level_opts.get("url") {
+        Some(url) if url.is_empty() => return Err("No value provided for flag --url!".to_string()),
+        Some(url) => Some(url.to_owned()),
+        None if top_level_opts.contains_key("socket") || top_level_opts.contains_key("port") => {
+            None
+        }
+        None => env::var("OCTYNE_URL").ok().filter(|url| !url.is_empty()),
+    };
+    match url {
+        Some(url) => {
+            let token = env::var("OCTYNE_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty())
+                .or_else(|| crate::utils::tokens::get_token(&url));
+            Transport::remote(&url, token)
+        }
+        None => Ok(Transport::Unix(octyne_path(top_level_opts)?)),
+    }
+}
+
+// Parses durations like 30s, 5m, 1h30m or 500ms, a plain number is treated as seconds.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration \"{}\"! (e.g. 30s, 5m, 1h30m)", text);
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+    let mut duration = Duration::ZERO;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let value = rest[..digits].parse::<u64>().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..unit] {
+            "ms" => Some(Duration::from_millis(value)),
+            "s" => Some(Duration::from_secs(value)),
+            "m" => value.checked_mul(60).map(Duration::from_secs),
+            "h" => value.checked_mul(60 * 60).map(Duration::from_secs),
+            "d" => value.checked_mul(24 * 60 * 60).map(Duration::from_secs),
+            _ => return Err(invalid()),
+        };
+        duration = part
+            .and_then(|part| duration.checked_add(part))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit..];
+    }
+    match text.is_empty() {
+        true => Err(invalid()),
+        false => Ok(duration),
+    }
+}
+
+// Instant after the given duration from now, clamped to the far future for huge durations.
+pub fn deadline(duration: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_add(duration)
+        .unwrap_or_else(|| now + FAR_FUTURE)
+}
+
+// Formats durations like "1 minute 30 seconds", the inverse of parse_duration for messages.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let units = [
+        (seconds / 86400, "day"),
+        (seconds / 3600 % 24, "hour"),
+        (seconds / 60 % 60, "minute"),
+        (seconds % 60, "second"),
+    ];
+    let parts = units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| match value {
+            1 => format!("1 {}", unit),
+            _ => format!("{} {}s", value, unit),
+        })
+        .collect::<Vec<String>>();
+    match parts.is_empty() {
+        true => format!("{} milliseconds", duration.as_millis()),
+        false => parts.join(" "),
+    }
+}
+
+// Number of apps to act on at once, 1 (i.e. one after the other) unless --parallel is passed.
+pub fn parallelism(opts: &HashMap<String, String>) -> usize {
+    match opts.get("parallel") {
+        Some(parallel) if parallel.is_empty() => DEFAULT_PARALLELISM,
+        // The parser only accepts numbers of at least 1.
+        Some(parallel) => parallel.parse().expect("--parallel is validated"),
+        None => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1d1s"), Ok(Duration::from_secs(86401)));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        for text in ["", "s", "5x", "m5", "1.5s", "-5s", "5s3"] {
+            assert!(
+                parse_duration(text).is_err(),
+                "{:?} should be invalid",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_durations() {
+        assert!(parse_duration("307445734561825861m").is_err());
+        assert!(parse_duration("213503982334602d").is_err());
+        assert!(parse_duration("18446744073709551615s18446744073709551615s").is_err());
+        assert_eq!(
+            parse_duration("18446744073709551615"),
+            Ok(Duration::from_secs(u64::MAX))
+        );
+        assert!(deadline(Duration::MAX) > Instant::now());
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(
+            format_duration(Duration::from_secs(90)),
+            "1 minute 30 seconds"
+        );
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2 hours");
+        assert_eq!(
+            format_duration(Duration::from_millis(250)),
+            "250 milliseconds"
+        );
+    }
 }
@@ -1,5 +1,13 @@
+pub mod actions;
+pub mod announce;
+pub mod cli;
+pub mod config;
+pub mod console_ui;
+pub mod history;
+pub mod line_editor;
+pub mod log_filter;
+pub mod man;
 pub mod misc;
-pub mod options;
-pub mod request;
-#[cfg(target_family = "windows")]
-pub mod unix_stream_windows;
+pub mod output;
+pub mod selector;
+pub mod tokens;
@@ -0,0 +1,188 @@
+use std::{collections::HashMap, fmt::Display, process::exit};
+
+use serde::Serialize;
+use serde_json::json;
+
+use octynectl::api::common::ApiError;
+
+pub const OUTPUTS: [&str; 2] = ["text", "json"];
+
+// With --output=json, every command prints a single JSON object to stdout instead of text (or one
+// JSON object per line for commands that stream output, like `console`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    Text,
+    Json,
+}
+
+impl Output {
+    pub fn from_opts(opts: &HashMap<String, String>) -> Output {
+        match opts.get("output").map(|output| output.as_str()) {
+            Some("json") => Output::Json,
+            _ => Output::Text,
+        }
+    }
+
+    // Finds --output in the command line, for errors from parsing it.
+    pub fn from_args(args: &[String]) -> Output {
+        let json = args
+            .iter()
+            .zip(args.iter().skip(1).chain([&String::new()]))
+            .any(|(arg, next)| arg == "--output=json" || (arg == "--output" && next == "json"));
+        match json {
+            true => Output::Json,
+            false => Output::Text,
+        }
+    }
+
+    pub fn is_json(self) -> bool {
+        self == Output::Json
+    }
+
+    // Prints `{"success": true}` in JSON mode, else the message.
+    pub fn success(self, message: impl Display) {
+        match self {
+            Output::Json => print_json(&json!({ "success": true })),
+            Output::Text => println!("{}", message),
+        }
+    }
+
+    // Prints `{context}: {error}` in text mode, or `{"error": ..., "exitCode": ...}` in JSON mode,
+    // then exits with the given exit code.
+    pub fn fail(self, context: &str, error: impl Display, exit_code: i32) -> ! {
+        match self {
+            Output::Json => print_json(&json!({
+                "success": false,
+                "error": error.to_string(),
+                "exitCode": exit_code,
+            })),
+            Output::Text => println!("{}: {}", context, error),
+        }
+        exit(exit_code);
+    }
+
+    // Prints a usage error as-is in text mode, or like `fail` in JSON mode, then exits with exit
+    // code 1.
+    pub fn fail_usage(self, error: impl Display) -> ! {
+        match self {
+            Output::Json => {
+                let error = error.to_string();
+                self.fail("Error", error.trim_start_matches("Error: "), 1)
+            }
+            Output::Text => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+    }
+
+    pub fn fail_api(self, context: &str, error: ApiError) -> ! {
+        let exit_code = error.exit_code();
+        self.fail(context, error, exit_code)
+    }
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionResult {
+    pub name: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+// Collects the result of an action on each app (or account, or node) for multi-target commands.
+// Errors are printed as they happen in text mode, and all results are printed at the end in JSON
+// mode as `{"success": ..., "results": [...]}`. The exit code is that of the last failure.
+pub struct Results {
+    output: Output,
+    results: Vec<ActionResult>,
+    exit_code: i32,
+    summary: bool,
+}
+
+impl Results {
+    pub fn new(output: Output) -> Results {
+        Results {
+            output,
+            results: Vec::new(),
+            exit_code: 0,
+            summary: false,
+        }
+    }
+
+    // In text mode, print the result for every target at the end instead of errors as they happen.
+    pub fn summary(mut self, summary: bool) -> Results {
+        self.summary = summary;
+        self
+    }
+
+    pub fn success(&mut self, name: &str) {
+        self.results.push(ActionResult {
+            name: name.to_owned(),
+            success: true,
+            error: None,
+            exit_code: None,
+        });
+    }
+
+    pub fn failure(&mut self, name: &str, context: &str, error: impl Display, exit_code: i32) {
+        if self.output == Output::Text && !self.summary {
+            println!("{}: {}", context, error);
+        }
+        self.results.push(ActionResult {
+            name: name.to_owned(),
+            success: false,
+            error: Some(error.to_string()),
+            exit_code: Some(exit_code),
+        });
+        self.exit_code = exit_code;
+    }
+
+    pub fn failure_api(&mut self, name: &str, context: &str, error: ApiError) {
+        let exit_code = error.exit_code();
+        self.failure(name, context, error, exit_code);
+    }
+
+    // Prints the results in JSON mode (or the message in text mode if all succeeded), and exits
+    // with the exit code of the last failure, if any.
+    pub fn finish(self, message: Option<&str>) {
+        match self.output {
+            Output::Json => print_json(&json!({
+                "success": self.exit_code == 0,
+                "results": self.results,
+            })),
+            Output::Text if self.summary => {
+                let succeeded = self.results.iter().filter(|result| result.success).count();
+                println!("{}/{} succeeded:", succeeded, self.results.len());
+                let longest_name = self.results.iter().map(|r| r.name.len()).max();
+                for result in self.results.iter() {
+                    let status = match &result.error {
+                        Some(error) => format!("Error: {}", error),
+                        None => "OK".to_string(),
+                    };
+                    println!(
+                        "    {:width$} | {}",
+                        result.name,
+                        status,
+                        width = longest_name.unwrap_or(0)
+                    );
+                }
+            }
+            Output::Text => {
+                if let (Some(message), 0) = (message, self.exit_code) {
+                    println!("{}", message);
+                }
+            }
+        }
+        if self.exit_code != 0 {
+            exit(self.exit_code);
+        }
+    }
+}
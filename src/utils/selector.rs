@@ -0,0 +1,252 @@
+use std::{collections::HashMap, process::exit};
+
+use serde_json::json;
+
+use crate::help::invalid_usage;
+use crate::utils::cli::Opt;
+use crate::utils::config::Node;
+use crate::utils::output::{print_json, Output};
+use octynectl::api::common::EXIT_APP_NOT_FOUND;
+use octynectl::api::server::{STATUS_CRASHED, STATUS_OFFLINE, STATUS_ONLINE};
+use octynectl::api::servers::{get_servers, ServerExtraInfo};
+
+pub const STATUSES: [&str; 3] = ["online", "offline", "crashed"];
+
+// A macro so that commands can append it to their own description with concat!.
+macro_rules! selector_help {
+    () => {
+        "
+Apps can be selected by name, or using glob patterns e.g. 'lobby-*' (quoted, so
+the shell does not expand them). Use --all to select all apps, --status to only
+select apps with a certain status, and --except to exclude apps (also accepts
+comma-separated names and patterns). Use --dry-run to see the selected apps."
+    };
+}
+pub(crate) use selector_help;
+
+pub const SELECTOR_HELP: &str = selector_help!();
+
+pub const ALL_OPTION: Opt = Opt::flag("all", "Select all apps").short('a');
+
+pub const STATUS_OPTION: Opt =
+    Opt::value("status", "status", "Only select apps with this status").choices(&STATUSES);
+
+pub const EXCEPT_OPTION: Opt = Opt::value(
+    "except",
+    "names",
+    "Exclude these apps from the selection
+(comma-separated names or patterns)",
+);
+
+pub const DRY_RUN_OPTION: Opt =
+    Opt::flag("dry-run", "Print the selected apps without acting on them");
+
+pub fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?', '['])
+}
+
+// Whether selectors (anything other than a list of literal app names) were used.
+pub fn uses_selectors(args: &[String], opts: &HashMap<String, String>) -> bool {
+    args.iter().any(|arg| is_pattern(arg))
+        || ["all", "status", "except"]
+            .iter()
+            .any(|key| opts.contains_key(*key))
+}
+
+// Resolves app names, patterns and selector options to a list of app names, querying Octyne only
+// if selectors are used. Literal names are kept even if they don't exist, so that commands report
+// them as not found. With --dry-run, the selected apps are printed and octynectl exits.
+pub async fn select_apps(
+    args: Vec<String>,
+    opts: &HashMap<String, String>,
+    node: &Node,
+    command: &str,
+) -> Vec<String> {
+    let output = Output::from_opts(opts);
+    let all = opts.contains_key("all");
+    if all && !args.is_empty() {
+        let error = "--all cannot be used with app names";
+        output.fail_usage(invalid_usage(error, command));
+    } else if args.is_empty() && !all && !opts.contains_key("status") {
+        output.fail_usage(invalid_usage("No apps specified", command));
+    }
+
+    let mut selected = Vec::new();
+    if !uses_selectors(&args, opts) {
+        selected = args;
+    } else {
+        let servers = get_servers(&node.client, true)
+            .await
+            .unwrap_or_else(|e| output.fail_api("Error", e))
+            .into_iter()
+            .map(|(name, value)| (name, ServerExtraInfo::from_value(value).status))
+            .collect::<Vec<(String, i64)>>();
+        let status = opts.get("status").map(|status| match status.as_str() {
+            "online" => STATUS_ONLINE as i64,
+            "crashed" => STATUS_CRASHED as i64,
+            _ => STATUS_OFFLINE as i64,
+        });
+        let excluded = opts
+            .get("except")
+            .map(|except| {
+                except
+                    .split(',')
+                    .map(|name| name.trim())
+                    .collect::<Vec<&str>>()
+            })
+            .unwrap_or_default();
+
+        let mut candidates = Vec::new();
+        if args.is_empty() {
+            candidates.extend(servers.iter().map(|(name, _)| name.clone()));
+        }
+        for arg in args {
+            if !is_pattern(&arg) {
+                candidates.push(arg);
+                continue;
+            }
+            let matches = servers
+                .iter()
+                .filter(|(name, _)| glob_match(&arg, name))
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<String>>();
+            if matches.is_empty() {
+                let error = format!("No apps match the pattern {}!", arg);
+                output.fail("Error", error, EXIT_APP_NOT_FOUND);
+            }
+            candidates.extend(matches);
+        }
+        for name in candidates {
+            let status_matches = match (status, servers.iter().find(|(n, _)| *n == name)) {
+                (Some(status), Some((_, app_status))) => *app_status == status,
+                _ => true,
+            };
+            if status_matches
+                && !selected.contains(&name)
+                && !excluded.iter().any(|pattern| glob_match(pattern, &name))
+            {
+                selected.push(name);
+            }
+        }
+    }
+
+    if opts.contains_key("dry-run") {
+        if output.is_json() {
+            print_json(&json!({ "dryRun": true, "apps": selected }));
+        } else if selected.is_empty() {
+            println!("No apps match the selection.");
+        } else {
+            println!("Would {} the following apps:", command);
+            for name in selected.iter() {
+                println!("    {}", name);
+            }
+        }
+        exit(0);
+    } else if selected.is_empty() {
+        if output.is_json() {
+            print_json(&json!({ "success": true, "results": [] }));
+        } else {
+            println!("No apps match the selection.");
+        }
+        exit(0);
+    }
+    selected
+}
+
+// Supports * (any characters), ? (any one character) and [...] (one of the characters, or a range
+// like [a-z], negated with [!...]).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let text = text.chars().collect::<Vec<char>>();
+    glob_match_chars(&pattern, &text)
+}
+
+fn glob_match_chars(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| glob_match_chars(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && glob_match_chars(&pattern[1..], &text[1..]),
+        Some('[') => {
+            // A ] right after [ or [! is part of the class, an unterminated [ matches itself.
+            let negated = matches!(pattern.get(1), Some('!' | '^'));
+            let start = if negated { 2 } else { 1 };
+            let Some(end) = pattern
+                .iter()
+                .skip(start + 1)
+                .position(|c| *c == ']')
+                .map(|i| i + start + 1)
+            else {
+                return text.first() == Some(&'[') && glob_match_chars(&pattern[1..], &text[1..]);
+            };
+            let Some(c) = text.first() else {
+                return false;
+            };
+            let class = &pattern[start..end];
+            let mut matched = false;
+            let mut index = 0;
+            while index < class.len() {
+                if index + 2 < class.len() && class[index + 1] == '-' {
+                    matched |= class[index] <= *c && *c <= class[index + 2];
+                    index += 3;
+                } else {
+                    matched |= class[index] == *c;
+                    index += 1;
+                }
+            }
+            matched != negated && glob_match_chars(&pattern[end + 1..], &text[1..])
+        }
+        Some(p) => text.first() == Some(p) && glob_match_chars(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(glob_match("lobby-*", "lobby-1"));
+        assert!(glob_match("lobby-*", "lobby-"));
+        assert!(!glob_match("lobby-*", "survival"));
+        assert!(glob_match("*-1", "lobby-1"));
+        assert!(glob_match("l*b*y", "lobby"));
+        assert!(glob_match("lobby-?", "lobby-2"));
+        assert!(!glob_match("lobby-?", "lobby-10"));
+        assert!(!glob_match("lobby", "lobby-1"));
+    }
+
+    #[test]
+    fn matches_classes() {
+        assert!(glob_match("lobby-[12]", "lobby-2"));
+        assert!(!glob_match("lobby-[12]", "lobby-3"));
+        assert!(glob_match("lobby-[1-3]", "lobby-3"));
+        assert!(!glob_match("lobby-[1-3]", "lobby-4"));
+        assert!(glob_match("lobby-[a-]", "lobby--"));
+        assert!(glob_match("[]x]", "]"));
+        assert!(!glob_match("lobby-[12]", "lobby-"));
+    }
+
+    #[test]
+    fn matches_negated_classes() {
+        assert!(glob_match("lobby-[!12]", "lobby-3"));
+        assert!(!glob_match("lobby-[!12]", "lobby-1"));
+        assert!(glob_match("lobby-[^1-3]", "lobby-4"));
+        assert!(!glob_match("[!]a]", "]"));
+        assert!(glob_match("[!]a]", "b"));
+    }
+
+    #[test]
+    fn matches_unterminated_class_literally() {
+        assert!(glob_match("lobby-[1", "lobby-[1"));
+        assert!(!glob_match("lobby-[1", "lobby-1"));
+        assert!(glob_match("[", "["));
+        assert!(glob_match("[!", "[!"));
+    }
+
+    #[test]
+    fn detects_patterns() {
+        assert!(is_pattern("lobby-*"));
+        assert!(is_pattern("lobby-[12]"));
+        assert!(!is_pattern("lobby-1"));
+    }
+}
@@ -0,0 +1,48 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+// Tokens are stored in a JSON file mapping Octyne URLs to tokens.
+pub fn tokens_path() -> Option<PathBuf> {
+    crate::utils::config::config_dir().map(|dir| dir.join("tokens.json"))
+}
+
+fn read_tokens() -> HashMap<String, String> {
+    tokens_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_tokens(tokens: &HashMap<String, String>) -> Result<(), String> {
+    let path = tokens_path().ok_or("Unable to find config directory!")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}! {}", parent.display(), e))?;
+    }
+    let contents = serde_json::to_string_pretty(tokens).map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}! {}", path.display(), e))?;
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to set permissions on {}! {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+pub fn get_token(url: &str) -> Option<String> {
+    read_tokens().remove(url.trim_end_matches('/'))
+}
+
+pub fn save_token(url: &str, token: &str) -> Result<(), String> {
+    let mut tokens = read_tokens();
+    tokens.insert(url.trim_end_matches('/').to_owned(), token.to_owned());
+    write_tokens(&tokens)
+}
+
+pub fn remove_token(url: &str) -> Result<(), String> {
+    let mut tokens = read_tokens();
+    if tokens.remove(url.trim_end_matches('/')).is_some() {
+        write_tokens(&tokens)?;
+    }
+    Ok(())
+}